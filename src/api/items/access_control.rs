@@ -0,0 +1,128 @@
+use serde_json::Value;
+use ::api::MultiOption;
+use ::error::{Result, Error, IoError, IoErrorKind};
+
+
+/// Access Control of the item granted to the user or the group
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AccessControl {
+    /// ID of the user or the group the access is granted to.
+    pub principal_id: String,
+    /// E-mail address of the user the access is granted to, `None` for groups.
+    pub principal_email: Option<String>,
+    /// Indicates if the principal can upload files into the folder.
+    pub can_upload: bool,
+    /// Indicates if the principal can download the item.
+    pub can_download: bool,
+    /// Indicates if the principal can delete the item.
+    pub can_delete: bool,
+    /// Indicates if the principal can manage permissions of the item.
+    pub can_manage: bool,
+}
+
+
+impl AccessControl {
+    /// Construct access control(s) from the decoded JSON value of the access control or
+    /// the access control collection. The empty collection is returned as `MultiOption::None`.
+    pub fn from_value(value: Value) -> Result<MultiOption<AccessControl>> {
+        // Check if we have one access control or many
+        match value.find("odata.count") {
+            Some(_) => {
+                let mut list = Vec::new();
+                if let Some(values) = value.find("value").and_then(|v| v.as_array()) {
+                    for val in values {
+                        match AccessControl::from_json(val.clone()) {
+                            Ok(access_control) => list.push(access_control),
+                            Err(e) => return Err(e)
+                        };
+                    }
+                }
+
+                if list.is_empty() {
+                    Ok(MultiOption::None)
+                }
+                else {
+                    Ok(MultiOption::Many(list))
+                }
+            },
+            None => {
+                AccessControl::from_json(value)
+                    .map(|access_control| MultiOption::One(access_control))
+            }
+        }
+    }
+
+    /// Construct access control from the decoded JSON value.
+    pub fn from_json(value: Value) -> Result<AccessControl> {
+        if let Some(object) = value.as_object() {
+            // Read principal
+            let principal = match object.get("Principal").and_then(|v| v.as_object()) {
+                Some(v) => v,
+                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "AccessControl.Principal property is missing.")),
+            };
+
+            let principal_id = match principal.get("Id").and_then(|v| v.as_str()) {
+                Some(v) => v,
+                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "AccessControl.Principal.Id property is missing.")),
+            };
+
+            let principal_email = principal.get("Email")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_owned());
+
+            // Read permissions (these properties are optional)
+            let flag = |name: &str| object.get(name)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            // Construct the AccessControl struct
+            Ok(AccessControl {
+                principal_id: principal_id.to_owned(),
+                principal_email: principal_email,
+                can_upload: flag("CanUpload"),
+                can_download: flag("CanDownload"),
+                can_delete: flag("CanDelete"),
+                can_manage: flag("CanManagePermissions"),
+            })
+        }
+        else {
+            Error::io_result(IoError::new(IoErrorKind::InvalidInput, "AccessControl can be constructed from JSON Object only."))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use ::api::MultiOption;
+    use super::AccessControl;
+
+    #[test]
+    fn access_controls_from_collection() {
+        let value = serde_json::from_str("{\"odata.count\":2,\"value\":[{\"Principal\":{\"odata.type\":\"ShareFile.Api.Models.User\",\"Id\":\"u-1\",\"Email\":\"jane@example.com\"},\"CanUpload\":true,\"CanDownload\":true,\"CanView\":true,\"CanDelete\":true,\"CanManagePermissions\":true},{\"Principal\":{\"odata.type\":\"ShareFile.Api.Models.Group\",\"Id\":\"g-1\"},\"CanDownload\":true}]}").unwrap();
+        match AccessControl::from_value(value).unwrap() {
+            MultiOption::Many(list) => {
+                assert_eq!(list[0], AccessControl {
+                    principal_id: "u-1".to_owned(),
+                    principal_email: Some("jane@example.com".to_owned()),
+                    can_upload: true,
+                    can_download: true,
+                    can_delete: true,
+                    can_manage: true,
+                });
+                assert_eq!(list[1].principal_id, "g-1");
+                assert_eq!(list[1].principal_email, None);
+                assert!(list[1].can_download);
+                assert!(!list[1].can_upload && !list[1].can_delete && !list[1].can_manage);
+            },
+            other => panic!("Unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn access_control_without_principal() {
+        let value = serde_json::from_str("{\"CanUpload\":true}").unwrap();
+        assert!(AccessControl::from_json(value).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+/// Resolution of the conflict with the file existing in the folder the file is uploaded into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UploadConflict {
+	/// Replace the existing file
+	Overwrite,
+	/// Keep the existing file and store the uploaded one under the unique name
+	Rename,
+	/// Keep the existing file and fail the upload with the conflict error
+	Skip,
+	/// Upload the file as the new version of the existing one
+	NewVersion,
+}
+
+
+impl UploadConflict {
+	/// Get the value of `overwrite` option of the upload request
+	pub fn overwrite(&self) -> bool {
+		match self {
+			&UploadConflict::Overwrite => true,
+			&UploadConflict::NewVersion => true,
+			_ => false
+		}
+	}
+}
+
+
+// The flag used before means overwrite the existing file or keep it
+impl From<bool> for UploadConflict {
+	fn from(overwrite: bool) -> UploadConflict {
+		if overwrite {
+			UploadConflict::Overwrite
+		}
+		else {
+			UploadConflict::Rename
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::UploadConflict;
+
+	#[test]
+	fn from_bool() {
+		assert_eq!(UploadConflict::from(true), UploadConflict::Overwrite);
+		assert_eq!(UploadConflict::from(false), UploadConflict::Rename);
+	}
+
+	#[test]
+	fn overwrite() {
+		assert!(UploadConflict::Overwrite.overwrite());
+		assert!(UploadConflict::NewVersion.overwrite());
+		assert!(!UploadConflict::Rename.overwrite());
+		assert!(!UploadConflict::Skip.overwrite());
+	}
+}
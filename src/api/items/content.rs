@@ -1,17 +1,34 @@
 use std::io;
+use std::cmp;
+use std::thread;
+use std::sync::{Arc, Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use hyper::method::Method;
+use hyper::header::{Headers, ContentLength, Range, ByteRangeSpec};
+use hyper::status::StatusCode;
 use md5;
+use serde_json::{self, Value};
 use rustc_serialize::hex::ToHex;
 use url::form_urlencoded;
-use ::error::{Result, Error, IoError, IoErrorKind};
+use ::error::{Result, Error, IoError, IoErrorKind, ServiceError};
 use ::connection::Connection;
 use ::odata::Parameters;
-use super::Path;
+use super::{Path, Item, Items};
 
 
 // Define the uploading data chunk size
 const CHUNK_SIZE: usize = 1024 * 1024;
 
+// Define how many times the data chunk is tried to upload in parallel uploads
+const CHUNK_ATTEMPTS: u32 = 3;
+
+// Define how many times the preparation status of the bulk download is checked
+const PREPARE_ATTEMPTS: u32 = 60;
+
+// Define the delay between checks of the preparation status of the bulk download
+const PREPARE_DELAY_MS: u64 = 500;
+
 
 struct WriteBuf {
 	conn: Connection,
@@ -21,6 +38,11 @@ struct WriteBuf {
 	chunk_uri: String,
 	chunk_no: u32,
 	chunk_size: usize,
+	finished: bool,
+	response: Option<String>,
+	progress: Option<Box<dyn FnMut(u64, u64)>>,
+	digest: md5::Context,
+	verify: bool,
 }
 
 
@@ -34,15 +56,399 @@ impl WriteBuf {
 			chunk_uri: chunk_uri,
 			chunk_no: 0,
 			chunk_size: chunk_size,
+			finished: false,
+			response: None,
+			progress: None,
+			digest: md5::Context::new(),
+			verify: false,
+		}
+	}
+
+	// Upload first `len` bytes of the buffered data as the next chunk. With `finish` set
+	// the chunk is the last one.
+	fn upload_chunk(&mut self, len: usize, finish: bool) -> io::Result<()> {
+		// Keep the running digest of the whole file
+		let mut digest = self.digest;
+		digest.consume(&self.buffer[0..len]);
+
+		let file_hash = if finish && self.verify {
+			Some(digest.compute().to_hex())
+		}
+		else {
+			None
+		};
+
+		match post_chunk(&self.conn, &self.chunk_uri, self.chunk_no, self.written, &self.buffer[0..len], finish, file_hash.as_ref().map(|h| h.as_str())) {
+			Ok(response) => {
+				self.written = self.written + len as u64;
+				self.chunk_no = self.chunk_no + 1;
+				self.digest = digest;
+				self.buffer.drain(0..len);
+
+				if finish {
+					self.finished = true;
+					self.response = Some(response);
+				}
+
+				if let Some(ref mut progress) = self.progress {
+					progress(self.written, self.size);
+				}
+
+				match file_hash {
+					Some(ref hash) => WriteBuf::verify_hash(self.response.as_ref().map(|r| r.as_str()).unwrap_or(""), hash),
+					None => Ok(())
+				}
+			},
+			Err(err) => Err(err)
+		}
+	}
+
+	// Compare the hash of the uploaded file reported in the finish response to the local one
+	fn verify_hash(response: &str, hash: &str) -> io::Result<()> {
+		let reported = serde_json::from_str::<Value>(response).ok()
+			.and_then(|v| v.find("value").and_then(|v| v.as_array()).and_then(|v| v.first()).and_then(|v| v.find("md5")).and_then(|v| v.as_str()).map(|v| v.to_lowercase()));
+
+		match reported {
+			Some(ref reported) if reported == hash => Ok(()),
+			Some(reported) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Uploaded file hash {} does not match local hash {}", reported, hash))),
+			None => Err(io::Error::new(io::ErrorKind::InvalidData, "Uploaded file hash is not reported"))
+		}
+	}
+}
+
+
+// Upload `data` as the chunk number `index` starting at `offset` of the file. With `finish`
+// set the chunk is the last one and `file_hash` if given is sent instead of the chunk hash.
+// Returns the response body. Failures are returned as `io::Error` wrapping the `Error`
+// which caused them so the status and the service message can be recovered.
+fn post_chunk(conn: &Connection, chunk_uri: &str, index: u32, offset: u64, data: &[u8], finish: bool, file_hash: Option<&str>) -> io::Result<String> {
+	// Compute MD5 digest of the data chunk
+	let hash = match file_hash {
+		Some(hash) => hash.to_owned(),
+		None => md5::compute(data).to_hex()
+	};
+
+	// Build chunk parameters
+	let mut params = form_urlencoded::Serializer::new(String::new());
+
+	if finish {
+		params.append_pair("finish", "true");
+	}
+
+	let params = params
+		.append_pair("index", &index.to_string())
+		.append_pair("offset", &offset.to_string())
+		.append_pair("filehash", &hash)
+		.finish();
+
+	// Upload the chunk
+	let mut url = chunk_uri.to_owned();
+	url.push('&');
+	url.push_str(&params);
+
+	match conn.custom_request(Method::Post, url, None, Some(data)) {
+		Ok(mut response) => {
+			let mut body = String::new();
+			if let Err(err) = io::Read::read_to_string(&mut response, &mut body) {
+				return Err(err);
+			}
+
+			if response.status.is_success() {
+				Ok(body)
+			}
+			else {
+				// Failed responses can contain JSON with the error details
+				let error = serde_json::from_str::<Value>(&body).ok()
+					.and_then(|value| ServiceError::from_json(&value))
+					.unwrap_or(ServiceError::new(None, format!("Chunk upload failed with status {}", response.status)));
+
+				Err(io::Error::new(io::ErrorKind::Other, Error::from(error.with_status(response.status))))
+			}
+		},
+		Err(err) => Err(io::Error::new(io::ErrorKind::Other, err.context("Cannot upload chunk")))
+	}
+}
+
+
+// Obtain upload specifications and return the URI the data chunks should be uploaded to.
+// With `base_file_id` given the file is uploaded as the new version of that file.
+fn request_chunk_uri(conn: &Connection, parent: Path, name: &str, size: u64, unzip: bool, overwrite: bool, base_file_id: Option<&str>) -> Result<String> {
+	let mut params = Parameters::new()
+		.custom(vec![
+			("method", "streamed"),
+			("raw", "true"),
+			("responseFormat", "json"),
+			("unzip", &::api::bool_to_string(unzip)),
+			("overwrite", &::api::bool_to_string(overwrite)),
+			("fileName", name),
+			("fileSize", &size.to_string()) ]);
+
+	if let Some(id) = base_file_id {
+		params = params.custom_add(("baseFileId", id));
+	}
+
+	let uri = parent.entity_and_parameters(Some("/Upload"), Some(params));
+
+	conn.query_json(Method::Get, uri, None, None)
+		.and_then(|specs| match specs.find("ChunkUri").and_then(|v| v.as_str()) {
+			Some(v) => Ok(v.to_owned()),
+			None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, "UploadSpecification.ChunkUri property is missing."))
+		})
+}
+
+
+// Obtain download specifications and return the temporary URL the data can be downloaded from
+pub fn request_download_url(conn: &Connection, path: Path) -> Result<String> {
+	let params = Parameters::new()
+		.custom_add(("redirect", "false"));
+
+	let uri = path.entity_and_parameters(Some("/Download"), Some(params));
+
+	conn.query_json(Method::Get, uri, None, None)
+		.and_then(|specs| match specs.find("DownloadUrl").and_then(|v| v.as_str()) {
+			Some(v) => Ok(v.to_owned()),
+			None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, "DownloadSpecification.DownloadUrl property is missing."))
+		})
+}
+
+
+// Obtain bulk download specifications of items `ids` in the folder `parent` and return
+// the temporary URL the zip archive can be downloaded from. If the archive is being prepared
+// the function waits until it's ready.
+fn request_zip_url(conn: &Connection, parent: Path, ids: &[String]) -> Result<String> {
+	let params = Parameters::new()
+		.custom(vec![
+			("ids", ids.join(",").as_str()),
+			("redirect", "false") ]);
+
+	let uri = parent.entity_and_parameters(Some("/BulkDownload"), Some(params));
+
+	let specs = match conn.query_json(Method::Get, uri, None, None) {
+		Ok(specs) => specs,
+		Err(err) => return Err(err)
+	};
+
+	if let Some(status_url) = specs.find("DownloadPrepStatusURL").and_then(|v| v.as_str()) {
+		if let Err(err) = wait_prepared(conn, status_url) {
+			return Err(err);
+		}
+	}
+
+	match specs.find("DownloadUrl").and_then(|v| v.as_str()) {
+		Some(v) => Ok(v.to_owned()),
+		None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, "DownloadSpecification.DownloadUrl property is missing."))
+	}
+}
+
+
+// Check the preparation status at `status_url` until the download is ready. The status URL
+// answers with `202 Accepted` while the download is being prepared.
+fn wait_prepared(conn: &Connection, status_url: &str) -> Result<()> {
+	for _ in 0..PREPARE_ATTEMPTS {
+		let status = match conn.custom_request(Method::Get, status_url.to_owned(), None, None) {
+			Ok(response) => response.status,
+			Err(err) => return Err(err)
+		};
+
+		if status == StatusCode::Accepted {
+			thread::sleep(Duration::from_millis(PREPARE_DELAY_MS));
+		}
+		else if status.is_success() {
+			return Ok(());
+		}
+		else {
+			return Error::other_result(format!("Download preparation failed with status {}", status));
+		}
+	}
+
+	Error::io_result(IoError::new(IoErrorKind::TimedOut, "Download preparation is not finished in time"))
+}
+
+
+// Get the ID of the uploaded item from the response to the chunk finishing the upload
+fn finish_response_item_id(response: &str) -> Result<String> {
+	let value: Value = match serde_json::from_str(response) {
+		Ok(value) => value,
+		Err(err) => return Err(Error::from(err))
+	};
+
+	if let Some(true) = value.find("error").and_then(|v| v.as_bool()) {
+		let message = value.find("errorMessage")
+			.and_then(|v| v.as_str())
+			.unwrap_or("Upload failed");
+		return Err(Error::from(message.to_owned()));
+	}
+
+	match value.find("value").and_then(|v| v.as_array()).and_then(|v| v.first()).and_then(|v| v.find("id")).and_then(|v| v.as_str()) {
+		Some(id) => Ok(id.to_owned()),
+		None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Upload response item id property is missing."))
+	}
+}
+
+
+// Read up to `len` bytes from `reader`. The chunk returned is shorter only if the end
+// of data is reached.
+fn read_chunk<R: io::Read>(reader: &mut R, len: usize) -> io::Result<Vec<u8>> {
+	let mut chunk = Vec::with_capacity(len);
+	match io::Read::read_to_end(&mut io::Read::take(reader, len as u64), &mut chunk) {
+		Ok(_) => Ok(chunk),
+		Err(err) => Err(err)
+	}
+}
+
+
+/// Upload `size` bytes read from `reader` into the folder identified by `parent` with up
+/// to `concurrency` data chunks uploaded at the same time. Each chunk is retried a few
+/// times if failed. The last chunk finishing the upload is sent after all other chunks
+/// are uploaded successfully. With `base_file_id` given the file is uploaded as the new
+/// version of that file. Most time there is no need to call the function directly.
+/// The better practice is to use method `upload_parallel()` of the `Items` instance.
+pub fn upload_parallel<R: io::Read>(conn: Connection, parent: Path, name: String, reader: &mut R, size: u64, unzip: bool, overwrite: bool, base_file_id: Option<&str>, chunk_size: Option<usize>, concurrency: usize) -> Result<()> {
+	let chunk_uri = match request_chunk_uri(&conn, parent, &name, size, unzip, overwrite, base_file_id) {
+		Ok(chunk_uri) => chunk_uri,
+		Err(err) => return Err(err)
+	};
+
+	let chunk_size = chunk_size.unwrap_or(CHUNK_SIZE);
+	let concurrency = cmp::max(concurrency, 1);
+
+	// Start workers which upload chunks queued and report failures
+	let (queue, jobs) = mpsc::sync_channel::<(u32, u64, Vec<u8>)>(concurrency);
+	let (report, failures) = mpsc::channel::<io::Error>();
+	let jobs = Arc::new(Mutex::new(jobs));
+	let failed = Arc::new(AtomicBool::new(false));
+
+	let workers: Vec<_> = (0..concurrency)
+		.map(|_| {
+			let conn = conn.clone();
+			let chunk_uri = chunk_uri.clone();
+			let jobs = jobs.clone();
+			let report = report.clone();
+			let failed = failed.clone();
+
+			thread::spawn(move || {
+				loop {
+					let job = match jobs.lock() {
+						Ok(jobs) => jobs.recv(),
+						Err(_) => return
+					};
+
+					let (index, offset, data) = match job {
+						Ok(job) => job,
+						Err(_) => return
+					};
+
+					let mut attempt = 1;
+					loop {
+						match post_chunk(&conn, &chunk_uri, index, offset, &data, false, None) {
+							Ok(_) => break,
+							Err(err) => {
+								if attempt >= CHUNK_ATTEMPTS {
+									failed.store(true, Ordering::SeqCst);
+									let _ = report.send(err);
+									return;
+								}
+
+								attempt = attempt + 1;
+							}
+						};
+					}
+				}
+			})
+		})
+		.collect();
+
+	// Only workers hold the queue receiver so queueing fails once all workers are gone
+	drop(jobs);
+	drop(report);
+
+	// Queue chunks holding back the last one which finishes the upload
+	let mut read_error = None;
+	let mut last: Option<(u32, u64, Vec<u8>)> = None;
+	let mut index = 0;
+	let mut offset = 0;
+
+	while offset < size && !failed.load(Ordering::SeqCst) {
+		let len = cmp::min(chunk_size as u64, size - offset) as usize;
+		let data = match read_chunk(reader, len) {
+			Ok(data) => data,
+			Err(err) => {
+				read_error = Some(err);
+				break;
+			}
+		};
+
+		if data.is_empty() {
+			break;
+		}
+
+		let read = data.len() as u64;
+		if let Some(chunk) = last.take() {
+			if queue.send(chunk).is_err() {
+				break;
+			}
+		}
+
+		last = Some((index, offset, data));
+		index = index + 1;
+		offset = offset + read;
+
+		if read < len as u64 {
+			break;
 		}
 	}
+
+	// Wait for workers to upload chunks queued
+	drop(queue);
+	for worker in workers {
+		let _ = worker.join();
+	}
+
+	if let Some(err) = read_error {
+		return Err(Error::from(err));
+	}
+
+	if let Ok(err) = failures.try_recv() {
+		return Err(Error::from(err));
+	}
+
+	// Finish the upload with the last chunk
+	let (index, offset, data) = last.unwrap_or((0, 0, Vec::new()));
+	let mut attempt = 1;
+	loop {
+		match post_chunk(&conn, &chunk_uri, index, offset, &data, true, None) {
+			Ok(_) => return Ok(()),
+			Err(err) => {
+				if attempt >= CHUNK_ATTEMPTS {
+					return Err(Error::from(err));
+				}
+
+				attempt = attempt + 1;
+			}
+		};
+	}
+}
+
+
+/// Obtain upload specifications and create the new instance of `Content` for writing data.
+/// With `base_file_id` given the file is uploaded as the new version of that file.
+pub fn open_upload(conn: Connection, parent: Path, name: String, size: u64, unzip: bool, overwrite: bool, base_file_id: Option<&str>, chunk_size: Option<usize>) -> Result<Content> {
+	// Try to obtain upload specifications and start uploading process
+	request_chunk_uri(&conn, parent, &name, size, unzip, overwrite, base_file_id)
+		.map(|chunk_uri| {
+			Content {
+				reader: None,
+				writer: Some(WriteBuf::new(conn, size, chunk_uri, chunk_size.unwrap_or(CHUNK_SIZE))),
+				length: None,
+			}
+		})
 }
 
 
 /// Item content reader/writer.  
 ///
-/// The struct implements `std::io::Read` and `std::io::Write` traits so it can be used
-/// neat abilities of the standard Rust library to read and write data.
+/// The struct implements `std::io::Read`, `std::io::BufRead` and `std::io::Write` traits
+/// so it can be used neat abilities of the standard Rust library to read and write data.
 ///
 /// ## Panics 
 /// The instance of `Content` can only read or only write data during the lifetime.
@@ -50,9 +456,16 @@ impl WriteBuf {
 /// will panic of it was created for writing.
 ///
 /// Also it can panic if the uploaded amount of bytes exceeded the file size given.
+///
+/// ## Finishing upload
+/// The upload is finished when the amount of bytes written reaches the file size given
+/// or when `flush()` is called, whichever comes first. Call `flush()` after all data is
+/// written to make sure the upload is finished even if the file size given was not exact,
+/// or call `finish()` which also returns the item uploaded.
 pub struct Content {
-	reader: Option<Box<io::Read>>,
+	reader: Option<io::BufReader<Box<io::Read>>>,
 	writer: Option<WriteBuf>,
+	length: Option<u64>,
 }
 
 
@@ -61,62 +474,130 @@ impl Content {
 	/// to create this struct directly. The better practice is to use method `download()`
 	/// of the `Items` instance.
 	pub fn open_for_read(conn: Connection, path: Path) -> Result<Self> {
-		let params = Parameters::new()
-			.custom_add(("redirect", "false"));
+		Content::open_download(conn, path, None)
+	}
+
+	/// Create the new instance of `Content` for reading the zip archive of items `ids`
+	/// which are children of the folder `parent`. Most time there is no need to create this
+	/// struct directly. The better practice is to use method `download_zip()`
+	/// of the `Items` instance.
+	pub fn open_for_read_zip(conn: Connection, parent: Path, ids: Vec<String>) -> Result<Self> {
+		request_zip_url(&conn, parent, &ids)
+			.and_then(|download_url| Content::open_stream(&conn, download_url, None))
+	}
+
+	/// Create the new instance of `Content` for reading the part of data starting at byte `start`
+	/// and ending at byte `end` inclusive, or at the end of data if `end` is `None`.
+	/// Most time there is no need to create this struct directly. The better practice
+	/// is to use method `download_range()` of the `Items` instance.
+	pub fn open_for_read_range(conn: Connection, path: Path, start: u64, end: Option<u64>) -> Result<Self> {
+		let range = match end {
+			Some(end) => ByteRangeSpec::FromTo(start, end),
+			None => ByteRangeSpec::AllFrom(start),
+		};
+
+		let mut headers = Headers::new();
+		headers.set(Range::Bytes(vec![range]));
 
-		let uri = path.entity_and_parameters(Some("/Download"), Some(params));
+		Content::open_download(conn, path, Some(headers))
+	}
 
+	// Obtain download specifications and open the stream sending `headers` given
+	fn open_download(conn: Connection, path: Path, headers: Option<Headers>) -> Result<Self> {
 		// Try to obtain download specifications and start downloading process
-		conn.query_json(Method::Get, uri, None, None)
-			.and_then(|specs| {
-				// Get download URL from the specs and open the stream
-				let download_url = match specs.find("DownloadUrl") {
-					Some(v) => v.as_str().unwrap(),
-					None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "DownloadSpecification.DownloadUrl property is missing."))
-				};
-
-				let reader = match conn.custom_request(Method::Get, download_url.to_owned(), None, None) {
-					Ok(response) => Box::new(response),
-					Err(err) => return Error::io_result(IoError::new(IoErrorKind::NotConnected, err))
-				};
-
-				Ok(Content {
-					reader: Some(reader),
-					writer: None,
-				})
-			})
+		request_download_url(&conn, path)
+			.and_then(|download_url| Content::open_stream(&conn, download_url, headers))
+	}
+
+	// Open the stream with the download URL from the specs
+	fn open_stream(conn: &Connection, download_url: String, headers: Option<Headers>) -> Result<Self> {
+		let response = match conn.custom_request(Method::Get, download_url, headers, None) {
+			Ok(response) => response,
+			Err(err) => return Error::io_result(IoError::new(IoErrorKind::NotConnected, err))
+		};
+
+		let length = response.headers.get::<ContentLength>()
+			.map(|&ContentLength(length)| length);
+
+		Ok(Content {
+			reader: Some(io::BufReader::new(Box::new(response))),
+			writer: None,
+			length: length,
+		})
 	}
 
 	/// Create the new instance of `Content` for writing data. Most time there is no need
 	/// to create this struct directly. The better practice is to use method `upload()`
 	/// of the `Items` instance.
 	pub fn open_for_write(conn: Connection, parent: Path, name: String, size: u64, unzip: bool, overwrite: bool, chunk_size: Option<usize>) -> Result<Self> {
-		let params = Parameters::new()
-			.custom(vec![
-				("method", "streamed"),
-				("raw", "true"),
-				("responseFormat", "json"),
-				("unzip", &::api::bool_to_string(unzip)),
-				("overwrite", &::api::bool_to_string(overwrite)),
-				("fileName", &name),
-				("fileSize", &size.to_string()) ]);
-
-		let uri = parent.entity_and_parameters(Some("/Upload"), Some(params));
-
-		// Try to obtain upload specifications and start uploading process
-		conn.query_json(Method::Get, uri, None, None)
-			.and_then(|specs| {
-				// Get download URL from the specs and open the stream
-				let chunk_uri = match specs.find("ChunkUri") {
-					Some(v) => v.as_str().unwrap(),
-					None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "UploadSpecification.ChunkUri property is missing."))
-				};
-
-				Ok(Content {
-					reader: None,
-					writer: Some(WriteBuf::new(conn, size, chunk_uri.to_owned(), chunk_size.unwrap_or(CHUNK_SIZE))),
-				})
-			})
+		open_upload(conn, parent, name, size, unzip, overwrite, None, chunk_size)
+	}
+
+	/// Get the amount of bytes to read as it reported by the server. In case of the ranged
+	/// download that is the length of the range. Returns `None` if the server does not
+	/// report the length or if `Content` is not opened for reading data.
+	pub fn len(&self) -> Option<u64> {
+		self.length
+	}
+
+	/// Set the callback which is called after each data chunk is uploaded with
+	/// the amount of bytes uploaded so far and the total amount of bytes to upload.
+	///
+	/// ## Panics
+	/// The method panics if `Content` is not opened for writing data.
+	pub fn set_progress_callback(&mut self, callback: Box<dyn FnMut(u64, u64)>) {
+		if let Some(ref mut write_buf) = self.writer {
+			write_buf.progress = Some(callback);
+		}
+		else {
+			panic!("Content stream is not opened for writing data");
+		}
+	}
+
+	/// Enable verification of the whole file MD5 hash. If enabled the hash of all data written
+	/// is sent when the upload is finished and compared to the hash the server reports back.
+	/// The upload finishing fails with the error on mismatch.
+	///
+	/// ## Panics
+	/// The method panics if `Content` is not opened for writing data.
+	pub fn set_verify_hash(&mut self, verify: bool) {
+		if let Some(ref mut write_buf) = self.writer {
+			write_buf.verify = verify;
+		}
+		else {
+			panic!("Content stream is not opened for writing data");
+		}
+	}
+
+	/// Finish the upload if it is not finished yet and return the item uploaded.
+	///
+	/// ## Panics
+	/// The method panics if `Content` is not opened for writing data.
+	pub fn finish(mut self) -> Result<Item> {
+		if let Err(err) = io::Write::flush(&mut self) {
+			return Err(Error::from(err));
+		}
+
+		let (conn, id) = match self.writer {
+			Some(WriteBuf { ref conn, response: Some(ref response), .. }) => match finish_response_item_id(response) {
+				Ok(id) => (conn.clone(), id),
+				Err(e) => return Err(e)
+			},
+			Some(_) => return Err(Error::from("The upload is not finished")),
+			None => panic!("Content stream is not opened for writing data")
+		};
+
+		Items::new(conn).stat(Path::Id(id), None)
+			.and_then(Items::single_item)
+	}
+
+	/// Get the amount of bytes uploaded so far and the total amount of bytes to upload.
+	/// Returns `(0, 0)` if `Content` is not opened for writing data.
+	pub fn progress(&self) -> (u64, u64) {
+		match self.writer {
+			Some(ref write_buf) => (write_buf.written, write_buf.size),
+			None => (0, 0)
+		}
 	}
 }
 
@@ -136,54 +617,55 @@ impl io::Read for Content {
 }
 
 
+// Implement std::io::BufRead trait
+// Panic in read operations if the content is not opened for read.
+impl io::BufRead for Content {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		if let Some(ref mut reader) = self.reader {
+			reader.fill_buf()
+		}
+		else {
+			panic!("Content stream is not opened for reading data");
+		}
+	}
+
+	fn consume(&mut self, amt: usize) {
+		if let Some(ref mut reader) = self.reader {
+			reader.consume(amt)
+		}
+		else {
+			panic!("Content stream is not opened for reading data");
+		}
+	}
+}
+
+
 // Implement std::io::Write trait
 // Panic in write operations if the content is not opened for write or 
 // if the uploaded amount of bytes exceeded those given in `size`.
 impl io::Write for Content {
 	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
 		if let Some(ref mut write_buf) = self.writer {
-			if write_buf.written < write_buf.size {
+			if write_buf.finished {
+				Err(io::Error::new(io::ErrorKind::Other, "Upload is finished already"))
+			}
+			else if write_buf.written < write_buf.size {
 				write_buf.buffer.extend_from_slice(buf);
-				let finish = (write_buf.written + write_buf.buffer.len() as u64) >= write_buf.size;
 
-				// Force upload if the chunk size is reached or the file size if reached
-				if write_buf.buffer.len() >= write_buf.chunk_size || finish {
-					// Compute MD5 digest of the data chunk
-					let digest = md5::compute(write_buf.buffer.as_slice());
-
-					// Build chunk parameters
-					let mut params = form_urlencoded::Serializer::new(String::new());
-
-					if finish {
-						params.append_pair("finish", "true");
+				// Upload full chunks while the chunk size is reached
+				while !write_buf.finished && write_buf.buffer.len() >= write_buf.chunk_size {
+					let chunk_size = write_buf.chunk_size;
+					let finish = (write_buf.written + chunk_size as u64) >= write_buf.size;
+					if let Err(err) = write_buf.upload_chunk(chunk_size, finish) {
+						return Err(err);
 					}
+				}
 
-					let params = params
-						.append_pair("index", &write_buf.chunk_no.to_string())
-						.append_pair("offset", &write_buf.written.to_string())
-						.append_pair("filehash", &digest.to_hex())
-						.finish();
-
-					// Upload the chunk
-					let mut url = write_buf.chunk_uri.clone();
-					url.push('&');
-					url.push_str(&params);
-
-					match write_buf.conn.custom_request(Method::Post, url, None, Some(write_buf.buffer.as_slice())) {
-						Ok(response) => {
-							if response.status.is_success() {
-								write_buf.written = write_buf.written + write_buf.buffer.len() as u64;
-								write_buf.chunk_no = write_buf.chunk_no + 1;
-								write_buf.buffer.clear();
-
-								Ok(buf.len())
-							}
-							else {
-								Err(io::Error::new(io::ErrorKind::Other, format!("Chunk upload failed with status {}", response.status)))
-							}
-						},
-						Err(_) => Err(io::Error::new(io::ErrorKind::Other, "Cannot upload chunk"))
-					}
+				// Force upload of the rest if the file size if reached
+				let len = write_buf.buffer.len();
+				if !write_buf.finished && len > 0 && (write_buf.written + len as u64) >= write_buf.size {
+					write_buf.upload_chunk(len, true)
+						.map(|_| buf.len())
 				}
 				else {
 					Ok(buf.len())
@@ -198,7 +680,19 @@ impl io::Write for Content {
 		}
 	}
 
-    fn flush(&mut self) -> io::Result<()> {
-    	Ok(())
-    }
+	// Upload the data buffered as the last chunk and finish the upload
+	fn flush(&mut self) -> io::Result<()> {
+		if let Some(ref mut write_buf) = self.writer {
+			if !write_buf.finished {
+				let len = write_buf.buffer.len();
+				write_buf.upload_chunk(len, true)
+			}
+			else {
+				Ok(())
+			}
+		}
+		else {
+			Ok(())
+		}
+	}
 }
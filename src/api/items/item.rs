@@ -1,10 +1,15 @@
 use chrono::prelude::*;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde_json::Value;
 use ::api::MultiOption;
 use super::{Path, Kind};
 use ::error::{Result, Error, IoError, IoErrorKind};
 
 
+// Define the namespace of item types in `odata.type` property
+const ODATA_MODELS: &'static str = "ShareFile.Api.Models.";
+
+
 /// Item details
 #[derive(Debug, Clone, PartialEq)]
 pub struct Item {
@@ -24,6 +29,16 @@ pub struct Item {
 	pub size: u64,
 	/// Item creation date and time
 	pub creation_date: DateTime<Utc>,
+	/// MD5 hash of the file contents
+	pub hash: Option<String>,
+	/// Date and time the item or any of its children was modified last time
+	pub progeny_edit_date: Option<DateTime<Utc>>,
+	/// Short name of the item creator
+	pub creator_name_short: Option<String>,
+	/// OData type of the item as it returned from ShareFile REST API, e.g.
+	/// `ShareFile.Api.Models.Folder`. Search results do not have the type so
+	/// that is `None` for them.
+	pub odata_type: Option<String>,
 	/// Meta information as it returned from ShareFile REST API
 	pub meta: Option<Value>,
 }
@@ -35,22 +50,42 @@ impl Item {
 		Path::Id(self.id.clone())
 	}
 
+	/// Test if the item is folder
+	pub fn is_folder(&self) -> bool {
+		self.kind.is_folder()
+	}
+
+	/// Test if the item is file
+	pub fn is_file(&self) -> bool {
+		self.kind.is_file()
+	}
+
 	/// Construct item(s) from the decoded JSON value. If `with_meta` is `true` then
 	/// `Item.meta` field of the each item will be filled with the JSON value
-	/// representing that item.
+	/// representing that item. The empty collection is returned as `MultiOption::None`.
 	pub fn from_value(value: Value, with_meta: bool) -> Result<MultiOption<Item>> {
 		// Check if we have one item or many
 		match value.find("odata.count") {
 			Some(_) => {
 				let mut items = Vec::new();
-				for val in value.find("value").unwrap().as_array().unwrap() {
+				let list = match value.find("value").and_then(|v| v.as_array()) {
+					Some(list) => list,
+					None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item collection value property is missing."))
+				};
+
+				for val in list {
 					match Item::item_from_value(val, with_meta) {
 						Ok(item) => items.push(item),
 						Err(e) => return Err(e)
 					};
 				}
 
-				Ok(MultiOption::Many(items))
+				if items.is_empty() {
+					Ok(MultiOption::None)
+				}
+				else {
+					Ok(MultiOption::Many(items))
+				}
 			},
 			None => {
 				match Item::item_from_value(&value, with_meta) {
@@ -61,64 +96,174 @@ impl Item {
 		}
 	}
 
-	fn item_from_value(value: &Value, with_meta: bool) -> Result<Item> {
+	/// Get the total amount of items from the decoded JSON value of the items collection.
+	/// The total is returned if the collection is requested with `$inlinecount` option.
+	pub fn count_from_value(value: &Value) -> Option<u64> {
+		value.find("odata.count")
+			.and_then(|v| v.as_u64())
+	}
+
+	/// Get the link to the next page of the items collection from the decoded JSON value.
+	/// The link is returned by the API if the collection is larger than the page it is
+	/// allowed to return with one response.
+	pub fn next_link_from_value(value: &Value) -> Option<String> {
+		value.find("odata.nextLink")
+			.and_then(|v| v.as_str())
+			.map(|link| String::from(link))
+	}
+
+	/// Construct items from the decoded JSON value of the search results. If `with_meta` is
+	/// `true` then `Item.meta` field of the each item will be filled with the JSON value
+	/// representing the search result for that item.
+	pub fn from_search_results(value: Value, with_meta: bool) -> Result<MultiOption<Item>> {
+		let results = match value.find("Results").and_then(|v| v.as_array()) {
+			Some(results) => results,
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "SearchResults.Results property is missing."))
+		};
+
+		let mut items = Vec::new();
+		for val in results {
+			match Item::item_from_search_result(val, with_meta) {
+				Ok(item) => items.push(item),
+				Err(e) => return Err(e)
+			};
+		}
+
+		if items.is_empty() {
+			Ok(MultiOption::None)
+		}
+		else {
+			Ok(MultiOption::Many(items))
+		}
+	}
+
+	// Search results describe items with their own set of properties
+	fn item_from_search_result(value: &Value, with_meta: bool) -> Result<Item> {
+		let read = |name: &str| value.find(name)
+			.and_then(|v| v.as_str())
+			.map(|v| v.to_owned());
+
 		// Which kind the item of
-		let kind = match value.find("odata.type") {
-			Some(otype) => match otype.as_str().unwrap() {
-				"ShareFile.Api.Models.Folder" => Kind::Folder,
-				"ShareFile.Api.Models.File" => Kind::File,
-				k => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Unknown item kind {}.", k)))
+		let kind = match value.find("ItemType").and_then(|v| v.as_str()) {
+			Some(k) => Kind::from_name(k),
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "SearchResult.ItemType property is missing."))
+		};
+
+		// Get item ID
+		let id = match read("ItemID") {
+			Some(v) => v,
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "SearchResult.ItemID property is missing."))
+		};
+
+		// Get item names, the display name is preferred
+		let filename = read("FileName").unwrap_or_default();
+		let name = read("DisplayName").unwrap_or_else(|| filename.clone());
+
+		// Get item creation date and time
+		let creation_date = match value.find("CreationDate").and_then(|v| v.as_str()) {
+			Some(v) => match v.parse::<DateTime<Utc>>() {
+				Ok(dt) => dt,
+				Err(err) => return Err(Error::from(err).context("SearchResult.CreationDate property is invalid"))
 			},
-			None => {
-				return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.odata.type property is missing."));
-			}
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "SearchResult.CreationDate property is missing."))
+		};
+
+		Ok(Item {
+			kind: kind,
+			id: id,
+			url: read("Url").unwrap_or_default(),
+			name: name,
+			filename: filename,
+			description: read("Details").unwrap_or_default(),
+			size: value.find("Size").and_then(|v| v.as_u64()).unwrap_or(0),
+			creation_date: creation_date,
+			hash: read("MD5"),
+			progeny_edit_date: None,
+			creator_name_short: None,
+			odata_type: None,
+			meta: if with_meta { Some(value.clone()) } else { None },
+		})
+	}
+
+	fn item_from_value(value: &Value, with_meta: bool) -> Result<Item> {
+		// Which kind the item of
+		let odata_type = match read_str(value, "odata.type") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
+		};
+
+		let kind = if odata_type.starts_with(ODATA_MODELS) {
+			Kind::from_name(&odata_type[ODATA_MODELS.len()..])
+		}
+		else {
+			Kind::from_name(odata_type)
 		};
 
 		// Get item ID
-		let id = match value.find("Id") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.Id property is missing."))
+		let id = match read_str(value, "Id") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
 		};
 
 		// Get item URL
-		let url = match value.find("url") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.url property is missing."))
+		let url = match read_str(value, "url") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
 		};
 
 		// Get item name
-		let name = match value.find("Name") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.Name property is missing."))
+		let name = match read_str(value, "Name") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
 		};
 
-		// Get item file name
-		let filename = match value.find("FileName") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.FileName property is missing."))
-		};
+		// Get item file name (folders often don't have one)
+		let filename = value.find("FileName")
+			.and_then(|v| v.as_str())
+			.unwrap_or("");
 
-		// Get item description
-		let description = match value.find("Description") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.Description property is missing."))
-		};
+		// Get item description (this property is optional)
+		let description = value.find("Description")
+			.and_then(|v| v.as_str())
+			.unwrap_or("");
 
 		// Get item size
 		let size = match value.find("FileSizeBytes") {
-			Some(v) => v.as_u64().unwrap(),
+			Some(v) => match v.as_u64() {
+				Some(v) => v,
+				None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.FileSizeBytes property must be a non-negative number."))
+			},
 			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.FileSizeBytes property is missing."))
 		};
 
 		// Get item creation date and time
-		let creation_date = match value.find("CreationDate") {
-			Some(v) => match v.as_str().unwrap().parse::<DateTime<Utc>>() {
+		let creation_date = match read_str(value, "CreationDate") {
+			Ok(v) => match v.parse::<DateTime<Utc>>() {
 				Ok(dt) => dt,
-				Err(err) => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Item.CreationDate property is invalid because {}", err)))
+				Err(err) => return Err(Error::from(err).context("Item.CreationDate property is invalid"))
 			},
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Item.CreationDate property is missing."))
+			Err(e) => return Err(e)
 		};
 
+		// Get item hash (this property is optional)
+		let hash = value.find("Hash")
+			.and_then(|v| v.as_str())
+			.map(|v| v.to_owned());
+
+		// Get item progeny edit date and time (this property is optional)
+		let progeny_edit_date = match value.find("ProgenyEditDate").and_then(|v| v.as_str()) {
+			Some(v) => match v.parse::<DateTime<Utc>>() {
+				Ok(dt) => Some(dt),
+				Err(err) => return Err(Error::from(err).context("Item.ProgenyEditDate property is invalid"))
+			},
+			None => None
+		};
+
+		// Get item creator short name (this property is optional)
+		let creator_name_short = value.find("CreatorNameShort")
+			.and_then(|v| v.as_str())
+			.map(|v| v.to_owned());
+
 		// Add meta to the item if requested
 		let meta = if with_meta {
 			Some(value.clone())
@@ -137,7 +282,227 @@ impl Item {
 			description: description.to_owned(),
 			size: size,
 			creation_date: creation_date,
+			hash: hash,
+			progeny_edit_date: progeny_edit_date,
+			creator_name_short: creator_name_short,
+			odata_type: Some(odata_type.to_owned()),
 			meta: meta,
 		})
 	}
 }
+
+
+// Read the required string property of the item
+fn read_str<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+	match value.find(name) {
+		Some(v) => match v.as_str() {
+			Some(v) => Ok(v),
+			None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Item.{} property must be a string.", name)))
+		},
+		None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Item.{} property is missing.", name)))
+	}
+}
+
+
+// Serialize with the same property names the API uses, the meta information is kept
+// as it is in `meta` property
+impl Serialize for Item {
+	fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+	where S: Serializer {
+		use serde_json::value::Map;
+		let mut object: Map<String, Value> = Map::new();
+
+		object.insert("odata.type".to_owned(), Value::String(format!("{}{}", ODATA_MODELS, self.kind.name())));
+		object.insert("Id".to_owned(), Value::String(self.id.clone()));
+		object.insert("url".to_owned(), Value::String(self.url.clone()));
+		object.insert("Name".to_owned(), Value::String(self.name.clone()));
+		object.insert("FileName".to_owned(), Value::String(self.filename.clone()));
+		object.insert("Description".to_owned(), Value::String(self.description.clone()));
+		object.insert("FileSizeBytes".to_owned(), Value::U64(self.size));
+		object.insert("CreationDate".to_owned(), Value::String(self.creation_date.to_rfc3339()));
+
+		if let Some(ref hash) = self.hash {
+			object.insert("Hash".to_owned(), Value::String(hash.clone()));
+		}
+
+		if let Some(ref progeny_edit_date) = self.progeny_edit_date {
+			object.insert("ProgenyEditDate".to_owned(), Value::String(progeny_edit_date.to_rfc3339()));
+		}
+
+		if let Some(ref creator_name_short) = self.creator_name_short {
+			object.insert("CreatorNameShort".to_owned(), Value::String(creator_name_short.clone()));
+		}
+
+		if let Some(ref meta) = self.meta {
+			object.insert("meta".to_owned(), meta.clone());
+		}
+
+		Value::Object(object).serialize(serializer)
+	}
+}
+
+
+// Deserialize through the JSON Value representation
+impl Deserialize for Item {
+	fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<Item, D::Error>
+	where D: Deserializer {
+		use serde::de::Error as DeError;
+
+		Value::deserialize(deserializer)
+			.and_then(|value| Item::item_from_value(&value, false)
+				.map(|mut item| {
+					item.meta = value.find("meta").cloned();
+					item
+				})
+				.map_err(|err| D::Error::custom(err.to_string())))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use serde_json::{self, Value};
+	use ::api::MultiOption;
+	use super::Item;
+
+	fn item_value() -> Value {
+		serde_json::from_str("{\"odata.type\":\"ShareFile.Api.Models.File\",\"Id\":\"fi-1\",\"url\":\"https://tenant.sf-api.com/sf/v3/Items(fi-1)\",\"Name\":\"a.txt\",\"FileName\":\"a.txt\",\"Description\":\"Notes\",\"FileSizeBytes\":1024,\"CreationDate\":\"2018-10-01T10:20:30.000Z\"}").unwrap()
+	}
+
+	fn parse(value: Value, with_meta: bool) -> Item {
+		match Item::from_value(value, with_meta).unwrap() {
+			MultiOption::One(item) => item,
+			other => panic!("Unexpected {:?}", other)
+		}
+	}
+
+	#[test]
+	fn item_with_optional_fields() {
+		let mut value = item_value();
+		{
+			let object = value.as_object_mut().unwrap();
+			object.insert("Hash".to_owned(), Value::String("0cc175b9c0f1b6a831c399e269772661".to_owned()));
+			object.insert("ProgenyEditDate".to_owned(), Value::String("2018-10-02T11:00:00.000Z".to_owned()));
+			object.insert("CreatorNameShort".to_owned(), Value::String("J. Doe".to_owned()));
+		}
+
+		let item = parse(value, false);
+		assert_eq!(item.hash, Some("0cc175b9c0f1b6a831c399e269772661".to_owned()));
+		assert_eq!(item.progeny_edit_date.map(|dt| dt.to_rfc3339()), Some("2018-10-02T11:00:00+00:00".to_owned()));
+		assert_eq!(item.creator_name_short, Some("J. Doe".to_owned()));
+	}
+
+	#[test]
+	fn item_without_optional_fields() {
+		let item = parse(item_value(), false);
+		assert_eq!(item.hash, None);
+		assert_eq!(item.progeny_edit_date, None);
+		assert_eq!(item.creator_name_short, None);
+	}
+
+	#[test]
+	fn item_kinds() {
+		let item = parse(item_value(), false);
+		assert!(item.is_file());
+		assert!(!item.is_folder());
+		assert_eq!(item.odata_type, Some("ShareFile.Api.Models.File".to_owned()));
+
+		let mut value = item_value();
+		value.as_object_mut().unwrap().insert("odata.type".to_owned(), Value::String("ShareFile.Api.Models.Folder".to_owned()));
+		let item = parse(value, false);
+		assert!(item.is_folder());
+		assert!(!item.is_file());
+		assert_eq!(item.odata_type, Some("ShareFile.Api.Models.Folder".to_owned()));
+		assert!(item.meta.is_none());
+	}
+
+	#[test]
+	fn item_link() {
+		use super::super::Kind;
+
+		let mut value = item_value();
+		value.as_object_mut().unwrap().insert("odata.type".to_owned(), Value::String("ShareFile.Api.Models.Link".to_owned()));
+		let item = parse(value, false);
+		assert_eq!(item.kind, Kind::Link);
+		assert!(!item.is_folder() && !item.is_file());
+
+		let json = serde_json::to_value(&item);
+		assert_eq!(json.find("odata.type").and_then(|v| v.as_str()), Some("ShareFile.Api.Models.Link"));
+	}
+
+	#[test]
+	fn item_unknown_kind() {
+		use super::super::Kind;
+
+		let list = format!("{{\"odata.count\":2,\"value\":[{},{}]}}",
+			item_value().to_string(),
+			item_value().to_string().replace("ShareFile.Api.Models.File", "ShareFile.Api.Models.Survey"));
+		match Item::from_value(serde_json::from_str(&list).unwrap(), false).unwrap() {
+			MultiOption::Many(items) => {
+				assert_eq!(items[0].kind, Kind::File);
+				assert_eq!(items[1].kind, Kind::Other("Survey".to_owned()));
+				assert_eq!(items[1].odata_type, Some("ShareFile.Api.Models.Survey".to_owned()));
+			},
+			other => panic!("Unexpected {:?}", other)
+		}
+	}
+
+	#[test]
+	fn item_empty_collection() {
+		let value = serde_json::from_str("{\"odata.count\":0,\"value\":[]}").unwrap();
+		assert_eq!(Item::from_value(value, false).unwrap(), MultiOption::None);
+	}
+
+	#[test]
+	fn item_with_invalid_types() {
+		let mut value = item_value();
+		value.as_object_mut().unwrap().insert("Id".to_owned(), Value::U64(42));
+		assert!(Item::from_value(value, false).is_err());
+
+		let mut value = item_value();
+		value.as_object_mut().unwrap().insert("FileSizeBytes".to_owned(), Value::String("1024".to_owned()));
+		assert!(Item::from_value(value, false).is_err());
+
+		let value = serde_json::from_str("{\"odata.count\":1,\"value\":{}}").unwrap();
+		assert!(Item::from_value(value, false).is_err());
+	}
+
+	#[test]
+	fn item_with_malformed_creation_date() {
+		use std::error::Error as StdError;
+		use ::error::{Error, DateError};
+
+		let mut value = item_value();
+		value.as_object_mut().unwrap().insert("CreationDate".to_owned(), Value::String("yesterday".to_owned()));
+
+		let err = Item::from_value(value, false).unwrap_err();
+		assert_eq!(err.description(), "Item.CreationDate property is invalid");
+
+		// The date parse error is preserved as the cause
+		let cause = err.source()
+			.and_then(|e| e.downcast_ref::<Error>());
+		match cause {
+			Some(&Error::Date(_)) => (),
+			other => panic!("Unexpected {:?}", other)
+		}
+		assert!(cause.and_then(|e| e.source()).and_then(|e| e.downcast_ref::<DateError>()).is_some());
+	}
+
+	#[test]
+	fn item_serde_round_trip() {
+		let item = parse(item_value(), false);
+		let json = serde_json::to_string(&item).unwrap();
+		let restored: Item = serde_json::from_str(&json).unwrap();
+		assert_eq!(restored, item);
+	}
+
+	#[test]
+	fn item_with_meta_serde_round_trip() {
+		let item = parse(item_value(), true);
+		assert!(item.meta.is_some());
+
+		let json = serde_json::to_string(&item).unwrap();
+		let restored: Item = serde_json::from_str(&json).unwrap();
+		assert_eq!(restored, item);
+	}
+}
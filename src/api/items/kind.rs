@@ -1,3 +1,6 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+
 /// Item kind
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Kind {
@@ -5,6 +8,14 @@ pub enum Kind {
 	Folder,
 	/// File
 	File,
+	/// Link to the external URL
+	Link,
+	/// Note
+	Note,
+	/// Symbolic link to another item
+	SymbolicLink,
+	/// Item of the kind unknown to the library with the kind name given
+	Other(String),
 }
 
 
@@ -24,12 +35,56 @@ impl Kind {
 			_ => false
 		}
 	}
+
+	/// Construct the kind from its name like `Folder`. Names unknown are kept in `Kind::Other`.
+	pub fn from_name(name: &str) -> Kind {
+		match name {
+			"Folder" => Kind::Folder,
+			"File" => Kind::File,
+			"Link" => Kind::Link,
+			"Note" => Kind::Note,
+			"SymbolicLink" => Kind::SymbolicLink,
+			other => Kind::Other(other.to_owned()),
+		}
+	}
+
+	/// Get the name of the kind
+	pub fn name(&self) -> &str {
+		match self {
+			&Kind::Folder => "Folder",
+			&Kind::File => "File",
+			&Kind::Link => "Link",
+			&Kind::Note => "Note",
+			&Kind::SymbolicLink => "SymbolicLink",
+			&Kind::Other(ref name) => name,
+		}
+	}
+}
+
+
+// Serialize as the kind name
+impl Serialize for Kind {
+	fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+	where S: Serializer {
+		serializer.serialize_str(self.name())
+	}
+}
+
+
+// Deserialize from the kind name
+impl Deserialize for Kind {
+	fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<Kind, D::Error>
+	where D: Deserializer {
+		String::deserialize(deserializer)
+			.map(|name| Kind::from_name(&name))
+	}
 }
 
 
 #[cfg(test)]
 mod tests {
 	use super::Kind;
+	use serde_json;
 
 	#[test]
 	fn test_is_folder() {
@@ -42,4 +97,13 @@ mod tests {
 		let kind = Kind::File;
 		assert!(kind.is_file());
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn kind_serde() {
+		assert_eq!(serde_json::to_string(&Kind::Folder).unwrap(), "\"Folder\"");
+		assert_eq!(serde_json::from_str::<Kind>("\"File\"").unwrap(), Kind::File);
+		assert_eq!(serde_json::from_str::<Kind>("\"Link\"").unwrap(), Kind::Link);
+		assert_eq!(serde_json::from_str::<Kind>("\"Survey\"").unwrap(), Kind::Other("Survey".to_owned()));
+		assert_eq!(serde_json::to_string(&Kind::Other("Survey".to_owned())).unwrap(), "\"Survey\"");
+	}
+}
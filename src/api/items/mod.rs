@@ -5,13 +5,22 @@ mod path;
 mod kind;
 mod item;
 mod content;
+mod pages;
+mod access_control;
+mod conflict;
 
-use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use chrono::Duration;
 use hyper::method::Method;
-use serde_json::Value;
-use ::connection::Connection;
+use hyper::status::StatusCode;
+use serde_json::{self, Value};
+use url::{Url, Position};
+use ::connection::{Connection, ConnectionHelper};
 use ::odata::Parameters;
 use ::api::MultiOption;
+use ::api::shares::{Shares, Share, ShareConfig};
+use ::error::ServiceError;
 use ::{Result, Error};
 
 
@@ -19,6 +28,9 @@ pub use self::path::Path;
 pub use self::kind::Kind;
 pub use self::item::Item;
 pub use self::content::Content;
+pub use self::pages::ItemPages;
+pub use self::access_control::AccessControl;
+pub use self::conflict::UploadConflict;
 
 
 /// Items Entity implementation.
@@ -28,6 +40,8 @@ pub struct Items {
     conn: Connection,
     meta: bool,
     upload_chunk_size: Option<usize>,
+    verify_upload: bool,
+    defaults: Option<Parameters>,
 }
 
 
@@ -38,6 +52,8 @@ impl Items {
             conn: conn,
             meta: false,
             upload_chunk_size: None,
+            verify_upload: false,
+            defaults: None,
         }
     }
 
@@ -47,9 +63,19 @@ impl Items {
             conn: conn,
             meta: meta,
             upload_chunk_size: upload_chunk_size,
+            verify_upload: false,
+            defaults: None,
         }
     }
 
+    /// Set the default `parameters` of all future API requests accepting parameters, like
+    /// `stat()`, `list()` and `search()`. The parameters given to each call are merged
+    /// into the defaults with `Parameters::merge()`.
+    pub fn with_defaults(mut self, parameters: Parameters) -> Self {
+        self.defaults = Some(parameters);
+        self
+    }
+
     /// Set the flag to inform all future API requests to include item meta information
     /// or omit it.
     pub fn include_meta(&mut self, include: bool) {
@@ -67,29 +93,37 @@ impl Items {
         self.upload_chunk_size = chunk_size;
     }
 
-    /// Resolve given `path` to the Item ID. On success returns `Some(Path::Id(id))`
-    /// and None otherwise.
-    pub fn resolve_path(&self, path: Path) -> Option<Path> {
+    /// Test if uploads are verified with the whole file MD5 hash.
+    pub fn verify_upload(&self) -> bool {
+        self.verify_upload
+    }
+
+    /// Set the flag to verify all future uploads with the whole file MD5 hash. See
+    /// `Content::set_verify_hash()` for details.
+    pub fn set_verify_upload(&mut self, verify: bool) {
+        self.verify_upload = verify;
+    }
+
+    /// Resolve given `path` to the Item ID. On success returns `Path::Id(id)`. The error
+    /// is returned if the item is not found or the API request fails.
+    pub fn resolve_path(&self, path: Path) -> Result<Path> {
         if let &Path::Id(_) = &path {
             // The path is the ID already
-            Some(path)
+            Ok(path)
         }
         else {
             // Resolve the path to the ID
-            match self.stat(path, None) {
-                Ok(MultiOption::One(item)) => Some(item.path()),
-                _ => None
-            }
+            self.stat(path, None)
+                .and_then(|items| Items::single_item(items))
+                .map(|item| item.path())
         }
     }
 
     /// Search for Item(s) at the `path` given and return found. `parameters` can be used
-    /// to provide additional options to the API request, like `includeDeleted`.
+    /// to provide additional options to the API request, like `Parameters::include_deleted`.
     pub fn stat(&self, path: Path, parameters: Option<Parameters>) -> Result<MultiOption<Item>> {
-        use std::error::Error;
-
-        self.get_items(path.entity_and_parameters(None, parameters))
-            .or_else(|err| if err.description().starts_with("NotFound") {
+        self.get_items(path.entity_and_parameters(None, self.parameters(parameters)))
+            .or_else(|err| if err.is_not_found() {
                     Ok(MultiOption::None)
                 }
                 else {
@@ -97,15 +131,70 @@ impl Items {
                 })
     }
 
+    /// Get the item identified by `id`. `parameters` can be used to provide additional
+    /// options to the API request. Unlike `stat()` the error is returned if the item
+    /// is not found or the API returns more than one item.
+    pub fn get(&self, id: &str, parameters: Option<Parameters>) -> Result<Item> {
+        self.get_items(Path::Id(id.to_owned()).entity_and_parameters(None, self.parameters(parameters)))
+            .and_then(|items| Items::single_item(items))
+    }
+
+    /// Get the item at the `path` given if it's changed since the version identified by `etag`
+    /// which is the value of `ETag` header returned with the item before. The request is sent
+    /// with `If-None-Match` header and `None` is returned if the API answers with
+    /// `304 Not Modified`. The changed item is returned with its new ETag which is passed
+    /// to the next check; pass `None` as `etag` to get the item and the ETag first time.
+    pub fn stat_if_changed(&self, path: Path, etag: Option<&str>) -> Result<Option<(Item, Option<String>)>> {
+        let mut headers = ConnectionHelper::json_headers();
+        if let Some(etag) = etag {
+            headers.set_raw("If-None-Match", vec![etag.as_bytes().to_vec()]);
+        }
+
+        match self.conn.query_full(Method::Get, path.entity_and_parameters(None, self.parameters(None)), Some(headers), None) {
+            Ok((_, headers, data)) => serde_json::from_str::<Value>(&data)
+                .or_else(|err| Error::json_result(err))
+                .and_then(|data| Error::from_json(data))
+                .and_then(|data| Item::from_value(data, self.meta))
+                .and_then(|items| Items::single_item(items))
+                .map(|item| {
+                    // Keep the new ETag for the next check
+                    let etag = headers.get_raw("ETag")
+                        .and_then(|values| values.first())
+                        .and_then(|value| String::from_utf8(value.clone()).ok());
+
+                    Some((item, etag))
+                }),
+            Err(ref err) if err.status() == Some(StatusCode::NotModified) => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Test if the item at the `path` given exists.
+    pub fn exists(&self, path: Path) -> Result<bool> {
+        self.stat(path, None)
+            .map(|items| !items.is_none())
+    }
+
+    /// Test if the item at the `path` given exists and is a folder.
+    pub fn is_folder(&self, path: Path) -> Result<bool> {
+        self.stat(path, None)
+            .map(|items| match items {
+                MultiOption::One(item) => item.kind.is_folder(),
+                _ => false
+            })
+    }
+
     /// List all items at the `path` given. `parameters` can be used
-    /// to provide additional options to the API request, like `includeDeleted`.
+    /// to provide additional options to the API request, like `Parameters::include_deleted`.
+    /// If the folder is larger than the page the API returns at once the next pages
+    /// are fetched following `odata.nextLink` until all children are listed.
     pub fn list(&self, path: Path, parameters: Option<Parameters>) -> Result<MultiOption<Item>> {
         match self.stat(path, None) {
             Ok(MultiOption::One(item)) => match item.kind {
                 Kind::Folder => {
-                    self.get_items(item.path().entity_and_parameters(Some("/Children"), parameters))
+                    self.get_all_items(item.path().entity_and_parameters(Some("/Children"), self.parameters(parameters)))
                 },
-                Kind::File => {
+                _ => {
                     Ok(MultiOption::One(item))
                 }
             },
@@ -114,35 +203,291 @@ impl Items {
         }
     }
 
+    /// List items at the `path` given like `list()` does and return the total amount of items
+    /// in the folder along with items. The total does not depend on `$top` and `$skip` options
+    /// so it can be obtained with a small page of items. `None` is returned as the total if
+    /// the `path` is not a folder.
+    pub fn list_counted(&self, path: Path, parameters: Option<Parameters>) -> Result<(MultiOption<Item>, Option<u64>)> {
+        match self.stat(path, None) {
+            Ok(MultiOption::One(item)) => match item.kind {
+                Kind::Folder => {
+                    let parameters = self.parameters(parameters).unwrap_or_default().count(true);
+                    self.conn.query_json(Method::Get, item.path().entity_and_parameters(Some("/Children"), Some(parameters)), None, None)
+                        .and_then(|data| {
+                            let count = Item::count_from_value(&data);
+                            Item::from_value(data, self.meta)
+                                .map(|items| (items, count))
+                        })
+                },
+                _ => {
+                    Ok((MultiOption::One(item), None))
+                }
+            },
+            Ok(other) => Ok((other, None)),
+            Err(e) => Err(e)
+        }
+    }
+
+    /// List children of the folder at the `path` given by `page_size` items per request.
+    /// The iterator returned yields the items of each page so large folders can be processed
+    /// without fetching all items at once.
+    pub fn list_paged(&self, path: Path, page_size: u32) -> ItemPages {
+        ItemPages::new(self.conn.clone(), self.meta, path, page_size)
+    }
+
+    /// List all descendants of the folder at the `path` given walking the subtree breadth-first.
+    /// Folders found are listed too, up to `max_depth` levels deep if given where the direct
+    /// children are on level 1. Items met more than once are listed only the first time.
+    /// `parameters` are applied to each folder listing.
+    pub fn list_recursive(&self, path: Path, parameters: Option<Parameters>, max_depth: Option<u32>) -> Result<Vec<Item>> {
+        let parameters = self.parameters(parameters);
+        let root = match self.stat(path, None) {
+            Ok(items) => match Items::single_item(items) {
+                Ok(item) => item,
+                Err(e) => return Err(e)
+            },
+            Err(e) => return Err(e)
+        };
+
+        if !root.kind.is_folder() {
+            return Ok(vec![root]);
+        }
+
+        let mut visited = BTreeSet::new();
+        let mut folders = VecDeque::new();
+        let mut descendants = Vec::new();
+
+        visited.insert(root.id.clone());
+        folders.push_back((root, 1));
+
+        while let Some((folder, depth)) = folders.pop_front() {
+            let children = match self.get_all_items(folder.path().entity_and_parameters(Some("/Children"), parameters.clone())) {
+                Ok(MultiOption::Many(children)) => children,
+                Ok(MultiOption::One(child)) => vec![child],
+                Ok(MultiOption::None) => Vec::new(),
+                Err(e) => return Err(e)
+            };
+
+            for child in children {
+                // Guard against cycles
+                if !visited.insert(child.id.clone()) {
+                    continue;
+                }
+
+                if child.kind.is_folder() && max_depth.map_or(true, |max| depth < max) {
+                    folders.push_back((child.clone(), depth + 1));
+                }
+
+                descendants.push(child);
+            }
+        }
+
+        Ok(descendants)
+    }
+
+    /// List all versions of the file at the `path` given. Each version is the separate
+    /// item with its own ID and creation date so it can be downloaded or removed
+    /// with `remove()` and `single_version` set.
+    pub fn versions(&self, path: Path) -> Result<MultiOption<Item>> {
+        self.resolve_path(path)
+            .and_then(|path| self.get_items(path.entity_and_parameters(Some("/Versions"), None)))
+    }
+
+    /// List users and groups having the access to the item at the `path` given
+    /// along with their permissions.
+    pub fn access_controls(&self, item: Path) -> Result<MultiOption<AccessControl>> {
+        self.resolve_path(item)
+            .and_then(|path| self.conn.query_json(Method::Get, path.entity_and_parameters(Some("/AccessControls"), None), None, None))
+            .and_then(|data| AccessControl::from_value(data))
+    }
+
+    /// Search for items matching `query` by name and content. `parameters` can be used
+    /// to provide additional options to the API request, like `$top` and `$skip` to page
+    /// through the results.
+    pub fn search(&self, query: &str, parameters: Option<Parameters>) -> Result<MultiOption<Item>> {
+        let parameters = self.parameters(parameters).unwrap_or_default()
+            .custom_add(("query", query));
+
+        let url = format!("Items/Search?{}", parameters.to_string());
+
+        self.conn.query_json(Method::Get, url, None, None)
+            .and_then(|data| Item::from_search_results(data, self.meta))
+    }
+
+    /// Get the parent folder of the item at the `path` given. The Home folder has no parent
+    /// so the error is returned for it.
+    pub fn parent(&self, path: Path) -> Result<Item> {
+        if path.is_home() {
+            return Err(Error::from("The Home folder has no parent"));
+        }
+
+        self.resolve_path(path)
+            .and_then(|path| self.get_items(Path::Parent(path.id()).entity_and_parameters(None, None)))
+            .and_then(|items| match items {
+                MultiOption::None => Err(Error::from("The Item has no parent")),
+                items => Items::single_item(items),
+            })
+    }
+
     /// Create folder with `parent` item and `name` given. On success returns the `Path` with the ID
     /// of the folder created.
     pub fn mkdir<T>(&self, parent: Path, name: T, description: Option<T>, overwite: bool) -> Result<Path>
     where T: Into<String> {
-        if let Some(path) = self.resolve_path(parent) {
-            // Prepare folder details
-            let mut data = BTreeMap::new();
-            data.insert(String::from("Name"), Value::String(name.into()));
+        self.create_folder(parent, name, description, overwite)
+            .and_then(|v| Path::from_json(v))
+    }
 
-            if let Some(desc) = description {
-                data.insert(String::from("Description"), Value::String(desc.into()));
-            }
+    /// Create folder with `parent` item and `name` given. On success returns the `Item`
+    /// of the folder created as ShareFile returned it.
+    pub fn mkdir_item<T>(&self, parent: Path, name: T, description: Option<T>, overwite: bool) -> Result<Item>
+    where T: Into<String> {
+        self.create_folder(parent, name, description, overwite)
+            .and_then(|data| Item::from_value(data, self.meta))
+            .and_then(|items| Items::single_item(items))
+    }
 
-            let body = Value::Object(data);
+    // Create the folder and return the JSON value of it
+    fn create_folder<T>(&self, parent: Path, name: T, description: Option<T>, overwite: bool) -> Result<Value>
+    where T: Into<String> {
+        let path = match self.resolve_path(parent) {
+            Ok(path) => path,
+            Err(e) => return Err(e.context("Cannot resolve parent ID"))
+        };
 
-            // Create folder
-            let parameters = Parameters::new()
-                .custom(vec![
-                    ("overwrite", super::bool_to_string(overwite)),
-                    ("passthrough", String::from("false")) ]);
+        // Prepare folder details
+        let mut data = BTreeMap::new();
+        data.insert(String::from("Name"), Value::String(name.into()));
 
-            let url = path.entity_and_parameters(Some("/Folder"), Some(parameters));
-            
-            self.conn.query_json(Method::Post, url, None, Some(body))
-                .and_then(|v| Path::from_json(v))
+        if let Some(desc) = description {
+            data.insert(String::from("Description"), Value::String(desc.into()));
         }
-        else {
-            Err(Error::from("Cannot resolve parent ID"))
+
+        let body = Value::Object(data);
+
+        // Create folder
+        let parameters = Parameters::new()
+            .custom(vec![
+                ("overwrite", super::bool_to_string(overwite)),
+                ("passthrough", String::from("false")) ]);
+
+        let url = path.entity_and_parameters(Some("/Folder"), Some(parameters));
+        
+        self.conn.query_json(Method::Post, url, None, Some(body))
+    }
+
+    /// Create all folders of the `relative` path like `a/b/c` inside the folder at `base`.
+    /// Folders existing already are skipped and those created get the `description` given.
+    /// On success returns the `Path` with the ID of the deepest folder. The error is returned
+    /// if some item on the path exists but is not a folder.
+    pub fn mkdir_p(&self, base: Path, relative: &str, description: Option<String>) -> Result<Path> {
+        let mut current = match self.resolve_path(base) {
+            Ok(path) => path,
+            Err(e) => return Err(e.context("Cannot resolve base folder ID"))
+        };
+
+        for name in relative.split('/').filter(|name| !name.is_empty()) {
+            current = match self.stat(Path::Relative(current.id(), name.to_owned()), None) {
+                Ok(MultiOption::One(item)) => if item.kind.is_folder() {
+                    item.path()
+                }
+                else {
+                    return Err(Error::Other(format!("The Item {} exists and is not a folder", name)))
+                },
+                Ok(MultiOption::Many(items)) => return Err(Items::ambiguous(items)),
+                Ok(MultiOption::None) => match self.mkdir(current, name.to_owned(), description.clone(), false) {
+                    Ok(path) => path,
+                    Err(e) => return Err(e)
+                },
+                Err(e) => return Err(e)
+            };
+        }
+
+        Ok(current)
+    }
+
+    /// Copy the item at `source` into the folder at `target_parent` on the server side.
+    /// On success returns the `Path` with the ID of the copy created.
+    ///
+    /// Folders are copied recursively with all their children. Copying of large folders
+    /// is performed asynchronously by ShareFile so children of the folder copy can appear
+    /// some time after the method returns.
+    pub fn copy(&self, source: Path, target_parent: Path, overwrite: bool) -> Result<Path> {
+        let source = match self.resolve_path(source) {
+            Ok(path) => path,
+            Err(e) => return Err(e)
+        };
+
+        let target_parent = match self.resolve_path(target_parent) {
+            Ok(path) => path,
+            Err(e) => return Err(e.context("Cannot resolve target parent ID"))
+        };
+
+        let parameters = Parameters::new()
+            .custom(vec![
+                ("targetid", target_parent.id()),
+                ("overwrite", super::bool_to_string(overwrite)) ]);
+
+        let url = source.entity_and_parameters(Some("/Copy"), Some(parameters));
+
+        self.conn.query_json(Method::Post, url, None, None)
+            .and_then(|v| Path::from_json(v))
+    }
+
+    /// Move the item at `item` into the folder at `new_parent`. On success returns
+    /// the moved item.
+    pub fn move_item(&self, item: Path, new_parent: Path, overwrite: bool) -> Result<Item> {
+        let item = match self.resolve_path(item) {
+            Ok(path) => path,
+            Err(e) => return Err(e)
+        };
+
+        let new_parent = match self.resolve_path(new_parent) {
+            Ok(path) => path,
+            Err(e) => return Err(e.context("Cannot resolve new parent ID"))
+        };
+
+        // Prepare new parent details
+        let mut parent = BTreeMap::new();
+        parent.insert(String::from("Id"), Value::String(new_parent.id()));
+
+        let mut data = BTreeMap::new();
+        data.insert(String::from("Parent"), Value::Object(parent));
+
+        let body = Value::Object(data);
+
+        // Move item
+        let parameters = Parameters::new()
+            .custom(vec![ ("overwrite", super::bool_to_string(overwrite)) ]);
+
+        let url = item.entity_and_parameters(None, Some(parameters));
+
+        self.conn.query_json(Method::Patch, url, None, Some(body))
+            .and_then(|data| Item::from_value(data, self.meta))
+            .and_then(|items| Items::single_item(items))
+    }
+
+    /// Rename the item at `item`. On success returns the renamed item.
+    pub fn rename(&self, item: Path, new_name: String) -> Result<Item> {
+        let mut changes = BTreeMap::new();
+        changes.insert(String::from("Name"), Value::String(new_name));
+        self.update(item, changes)
+    }
+
+    /// Update properties of the item at `item`, e.g. `Name` or `Description`, with the values
+    /// of `changes` given. On success returns the updated item.
+    pub fn update(&self, item: Path, changes: BTreeMap<String, Value>) -> Result<Item> {
+        if changes.is_empty() {
+            return Err(Error::from("There are no changes to update the Item with"));
         }
+
+        self.resolve_path(item)
+            .and_then(|path| {
+                let url = path.entity_and_parameters(None, None);
+
+                self.conn.query_json(Method::Patch, url, None, Some(Value::Object(changes)))
+                    .and_then(|data| Item::from_value(data, self.meta))
+                    .and_then(|items| Items::single_item(items))
+            })
     }
 
     /// Removes the item with the ID given. `single_version` set to `true` will delete
@@ -151,7 +496,6 @@ impl Items {
     /// asynchronously.
     pub fn remove(&self, path: Path, single_version: bool, force_sync: bool) -> Result<()> {
         self.resolve_path(path)
-            .ok_or(Error::from("The Item is not found"))
             .and_then(|path| {
                 let parameters = Parameters::new()
                     .custom(vec![
@@ -165,24 +509,63 @@ impl Items {
             })
     }
 
+    /// Restore the item at `path` from the Recycle Bin. On success returns the restored item.
+    /// Recycled items usually can't be found by path so `path` should be `Path::Id`.
+    ///
+    /// The item is restored into its original parent folder so the restore may fail if
+    /// the parent doesn't exist anymore.
+    pub fn restore(&self, path: Path) -> Result<Item> {
+        self.resolve_path(path)
+            .and_then(|path| {
+                let url = path.entity_and_parameters(Some("/Restore"), None);
+
+                self.conn.query_json(Method::Post, url, None, None)
+                    .and_then(|data| Item::from_value(data, self.meta))
+                    .and_then(|items| Items::single_item(items))
+            })
+    }
+
+    /// Remove all items from the Recycle Bin permanently.
+    pub fn empty_recycle_bin(&self) -> Result<()> {
+        self.conn.query_string(Method::Post, String::from("Items/RecycleBin/Empty"), None, None)
+            .map(|_| ())
+    }
+
     /// Removes multiple items. All items in bulk delete must be children of the same parent.
     /// `delete_premanently` set to `true` will remove items from
     /// the Recycle Bin or bypass it entirely and `force_sync` set to `true` will block
     /// the operation from taking place asynchronously.
-    pub fn remove_bulk(&self, parent: Path, items: Vec<Path>, delete_premanently: bool, force_sync: bool) -> Result<()> {
+    ///
+    /// Items which are not found are not removed; on success their paths are returned
+    /// so the empty list means all items are removed. Other failures of resolving items,
+    /// like network errors, fail the whole operation and nothing is removed.
+    pub fn remove_bulk(&self, parent: Path, items: Vec<Path>, delete_premanently: bool, force_sync: bool) -> Result<Vec<Path>> {
         self.resolve_path(parent)
-            .ok_or(Error::from("The Parent Item is not found"))
+            .map_err(|e| e.context("The Parent Item is not found"))
             .and_then(|parent| {
-                // Prepare item list
-                let items: Vec<Value> = items.into_iter()
-                    .map(|path| self.resolve_path(path)) // Resolve each Path into an Item ID
-                    .filter(|path| path.is_some()) // Remove not resolved Paths
-                    .map(|path| path.unwrap()) // Extract Paths from Option
-                    .filter(|path| path.is_id()) // Remove those Paths which does not contain the ID
-                    .map(|path| Value::String(path.id())) // Extract Item IDs and convert into Value::String
-                    .collect();
+                // Prepare item list resolving each Path into an Item ID and collecting not resolved Paths
+                let mut unresolved = Vec::new();
+                let mut ids = Vec::new();
+                for path in items {
+                    if path.is_id() {
+                        ids.push(Value::String(path.id()));
+                        continue;
+                    }
+
+                    match self.stat(path.clone(), None) {
+                        Ok(MultiOption::One(item)) => ids.push(Value::String(item.id)),
+                        Ok(MultiOption::None) => unresolved.push(path),
+                        Ok(MultiOption::Many(items)) => return Err(Items::ambiguous(items)),
+                        Err(ref e) if e.is_not_found() => unresolved.push(path),
+                        Err(e) => return Err(e)
+                    }
+                }
+
+                if ids.is_empty() {
+                    return Ok(unresolved);
+                }
 
-                let body = Value::Array(items);
+                let body = Value::Array(ids);
 
                 // Perform Bulk Delete
                 let parameters = Parameters::new()
@@ -192,7 +575,102 @@ impl Items {
 
                 let url = parent.entity_and_parameters(Some("/BulkDelete"), Some(parameters));
 
-                self.conn.query_json(Method::Post, url, None, Some(body))
+                self.conn.query_string(Method::Post, url, None, Some(body.to_string()))
+                    .map(|_| unresolved)
+            })
+    }
+
+    /// Create the `Send` share of the item at `item` and return it. The link to the share
+    /// is in `Share.uri`. The share expires after `expires_in` if given and only
+    /// authenticated users can download the item if `require_login` is set.
+    pub fn share_link(&self, item: Path, expires_in: Option<Duration>, require_login: bool) -> Result<Share> {
+        let item = match self.resolve_path(item) {
+            Ok(path) => path,
+            Err(e) => return Err(e)
+        };
+
+        let mut config = ShareConfig::send()
+            .items(vec![item])
+            .require_login(require_login);
+
+        if let Some(duration) = expires_in {
+            config = config.expires_in(duration);
+        }
+
+        Shares::new(self.conn.clone()).create(&config, false)
+    }
+
+    /// Add the item at `item` to the Favorites collection of the user the connection is
+    /// authenticated as. Duplicates are idempotent on the server so adding the item which
+    /// is in favorites already has no effect.
+    pub fn add_favorite(&self, item: Path) -> Result<()> {
+        self.resolve_path(item)
+            .and_then(|path| {
+                let mut item = BTreeMap::new();
+                item.insert(String::from("Id"), Value::String(path.id()));
+
+                let mut data = BTreeMap::new();
+                data.insert(String::from("Item"), Value::Object(item));
+
+                self.conn.query_json(Method::Post, String::from("Users(me)/FavoriteFolders"), None, Some(Value::Object(data)))
+                    .map(|_| ())
+            })
+    }
+
+    /// Remove the item at `item` from the Favorites collection of the user the connection is
+    /// authenticated as.
+    pub fn remove_favorite(&self, item: Path) -> Result<()> {
+        self.resolve_path(item)
+            .and_then(|path| {
+                let url = format!("Users(me)/FavoriteFolders({})", path.id());
+
+                self.conn.query_string(Method::Delete, url, None, None)
+                    .map(|_| ())
+            })
+    }
+
+    /// Check out the file at `item` so other users cannot modify it until it's checked in.
+    /// Fails with the conflict error (see `Error::is_conflict`) if the file is already
+    /// checked out by another user.
+    pub fn check_out(&self, item: Path) -> Result<()> {
+        self.check_action(item, "/CheckOut")
+            .map_err(|err| if err.is_conflict() {
+                    err.context("The Item is already checked out by another user")
+                }
+                else {
+                    err
+                })
+    }
+
+    /// Check in the file at `item` previously checked out with `check_out`.
+    /// With `keep_checked_out` set to `true` the file is checked out again right after
+    /// the check in, so the changes are published and the lock is retained.
+    pub fn check_in(&self, item: Path, keep_checked_out: bool) -> Result<()> {
+        self.resolve_path(item)
+            .and_then(|path| {
+                self.check_action(path.clone(), "/CheckIn")
+                    .and_then(|_| if keep_checked_out {
+                            self.check_out(path)
+                        }
+                        else {
+                            Ok(())
+                        })
+            })
+    }
+
+    /// Discard the check out of the file at `item` releasing the lock without publishing
+    /// the changes.
+    pub fn discard_check_out(&self, item: Path) -> Result<()> {
+        self.check_action(item, "/DiscardCheckOut")
+    }
+
+    // Perform check in/out `action` on the item
+    fn check_action(&self, item: Path, action: &str) -> Result<()> {
+        self.resolve_path(item)
+            .and_then(|path| {
+                let url = path.entity_and_parameters(Some(action), None);
+
+                self.conn.query_string(Method::Post, url, None, None)
                     .map(|_| ())
             })
     }
@@ -225,15 +703,124 @@ impl Items {
             // We have a path which should be resolved to the id first
             match self.stat(path, None) {
                 Ok(MultiOption::One(item)) => Content::open_for_read(self.conn.clone(), item.path()),
-                Ok(MultiOption::Many(_)) => Err(Error::from("There are more than one Item on path")),
+                Ok(MultiOption::Many(items)) => Err(Items::ambiguous(items)),
+                Ok(MultiOption::None) => Err(Error::from("The Item is not found")),
+                Err(e) => Err(e)
+            }
+        }
+    }
+
+    /// Download all children of the folder identified by `folder` as one zip archive.
+    /// If the API prepares the archive asynchronously the method waits until it's ready.
+    pub fn download_zip(&self, folder: Path) -> Result<Content> {
+        let folder = match self.stat(folder, None) {
+            Ok(items) => match Items::single_item(items) {
+                Ok(item) => item,
+                Err(e) => return Err(e)
+            },
+            Err(e) => return Err(e)
+        };
+
+        if !folder.kind.is_folder() {
+            return Err(Error::from("The Item is not a folder"));
+        }
+
+        let ids: Vec<String> = match self.get_all_items(folder.path().entity_and_parameters(Some("/Children"), None)) {
+            Ok(children) => children.into_iter().map(|item| item.id).collect(),
+            Err(e) => return Err(e)
+        };
+
+        if ids.is_empty() {
+            return Err(Error::from("The folder has no items to download"));
+        }
+
+        Content::open_for_read_zip(self.conn.clone(), folder.path(), ids)
+    }
+
+    /// Download the part of the item identified by `path` starting at byte `start` and ending
+    /// at byte `end` inclusive, or at the end of the item if `end` is `None`. The method can be
+    /// used to resume the download interrupted.
+    pub fn download_range(&self, path: Path, start: u64, end: Option<u64>) -> Result<Content> {
+        if path.is_id() {
+            // We have the ID already so just start download
+            Content::open_for_read_range(self.conn.clone(), path, start, end)
+        }
+        else {
+            // We have a path which should be resolved to the id first
+            match self.stat(path, None) {
+                Ok(MultiOption::One(item)) => Content::open_for_read_range(self.conn.clone(), item.path(), start, end),
+                Ok(MultiOption::Many(items)) => Err(Items::ambiguous(items)),
                 Ok(MultiOption::None) => Err(Error::from("The Item is not found")),
                 Err(e) => Err(e)
             }
         }
     }
 
+    /// Get the temporary URL the item at the `path` given can be downloaded from directly,
+    /// e.g. by another process. The URL expires after some time.
+    pub fn download_url(&self, path: Path) -> Result<String> {
+        self.resolve_path(path)
+            .and_then(|path| content::request_download_url(&self.conn, path))
+    }
+
+    /// Get the thumbnail image of the item at the `path` given. `size` is the size of the
+    /// thumbnail side in pixels, ShareFile supports `75` and `600`. The error is returned
+    /// if the item has no thumbnail e.g. it is the folder or the file which is not
+    /// an image or a document.
+    pub fn thumbnail(&self, path: Path, size: u32) -> Result<Vec<u8>> {
+        let path = match self.resolve_path(path) {
+            Ok(path) => path,
+            Err(e) => return Err(e)
+        };
+
+        let parameters = Parameters::new()
+            .custom(vec![
+                ("size", size.to_string()),
+                ("redirect", String::from("false")) ]);
+
+        let url = path.entity_and_parameters(Some("/Thumbnail"), Some(parameters));
+
+        // Obtain the redirection to the thumbnail image
+        let uri = match self.conn.query_json(Method::Get, url, None, None) {
+            Ok(value) => match value.find("Uri").and_then(|v| v.as_str()) {
+                Some(uri) => uri.to_owned(),
+                None => return Err(Error::from("The Item has no thumbnail"))
+            },
+            Err(e) => return Err(if e.is_not_found() {
+                    e.context("The Item has no thumbnail")
+                }
+                else {
+                    e
+                })
+        };
+
+        // Download the image
+        let mut response = match self.conn.custom_request(Method::Get, uri, None, None) {
+            Ok(response) => response,
+            Err(e) => return Err(e)
+        };
+
+        if !response.status.is_success() {
+            return Err(Error::Other(format!("Thumbnail download failed with status {}", response.status)));
+        }
+
+        let mut data = Vec::new();
+        response.read_to_end(&mut data)
+            .map(|_| data)
+            .map_err(Error::from)
+    }
+
+    /// Download the content of the file at the `path` given into `out`. Returns the amount
+    /// of bytes copied.
+    pub fn download_to_writer<W: Write>(&self, path: Path, out: &mut W) -> Result<u64> {
+        self.download(path)
+            .and_then(|mut content| io::copy(&mut content, out).map_err(Error::from))
+    }
+
     /// Upload the local file/stream into the folder identified by `parent`. The method
     /// returns the writer which can be used to write data in any convenient manner.
+    /// `conflict` tells what to do if the file `name` exists in the folder already, see
+    /// `UploadConflict`. The `bool` is accepted also, `true` means overwrite the file.
     ///
     /// The snippet of how the local file can be uploaded.
     ///
@@ -244,7 +831,7 @@ impl Items {
     /// let mut file = BufReader::new(file);
     ///
     /// let parent = Path::Absolute(String::from("/my_folder"));
-    /// let mut stream = items.upload(parent, String::from("file.txt"), metadata.len(), false, true).unwrap();
+    /// let mut stream = items.upload(parent, String::from("file.txt"), metadata.len(), false, UploadConflict::Overwrite).unwrap();
     /// let mut buf = [0; 1024];
     ///
     /// loop {
@@ -254,20 +841,112 @@ impl Items {
     ///         Err(err) => panic!("{:?}", err)
     ///     };
     /// }
+    ///
+    /// // Finish the upload
+    /// stream.flush().unwrap();
     /// ```
-    pub fn upload(&self, parent: Path, name: String, size: u64, unzip: bool, overwite: bool) -> Result<Content> {
-        if parent.is_id() {
-            // We have the ID already so just start download
-            Content::open_for_write(self.conn.clone(), parent, name, size, unzip, overwite, self.upload_chunk_size)
+    pub fn upload<C>(&self, parent: Path, name: String, size: u64, unzip: bool, conflict: C) -> Result<Content> where C: Into<UploadConflict> {
+        self.open_upload(parent, name, size, unzip, conflict.into())
+            .map(|mut content| {
+                content.set_verify_hash(self.verify_upload);
+                content
+            })
+    }
+
+    // Resolve the parent folder and open the upload stream
+    fn open_upload(&self, parent: Path, name: String, size: u64, unzip: bool, conflict: UploadConflict) -> Result<Content> {
+        match self.upload_target(parent, &name, conflict) {
+            Ok((parent, base_file_id)) => content::open_upload(self.conn.clone(), parent, name, size, unzip, conflict.overwrite(), base_file_id.as_ref().map(|id| id.as_str()), self.upload_chunk_size),
+            Err(e) => Err(e)
         }
-        else {
-            // We have a path which should be resolved to the id first
-            match self.stat(parent, None) {
-                Ok(MultiOption::One(item)) => Content::open_for_write(self.conn.clone(), item.path(), name, size, unzip, overwite, self.upload_chunk_size),
-                Ok(MultiOption::Many(_)) => Err(Error::from("There are more than one Item on path")),
-                Ok(MultiOption::None) => Err(Error::from("The Item is not found")),
-                Err(e) => Err(e)
-            }
+    }
+
+    // Resolve the parent folder the file `name` is uploaded into and the ID of the file
+    // the new version is uploaded of, if any
+    fn upload_target(&self, parent: Path, name: &str, conflict: UploadConflict) -> Result<(Path, Option<String>)> {
+        let parent = match self.resolve_path(parent) {
+            Ok(parent) => parent,
+            Err(e) => return Err(e)
+        };
+
+        // The API resolves overwrite and rename itself, for other modes the existing
+        // file should be looked up first
+        let base_file_id = match conflict {
+            UploadConflict::Skip | UploadConflict::NewVersion => {
+                match self.stat(Path::Relative(parent.id(), name.to_owned()), None) {
+                    Ok(MultiOption::None) => None,
+                    Ok(MultiOption::One(item)) => {
+                        if conflict == UploadConflict::Skip {
+                            return Error::service_result(ServiceError::new(Some("Conflict".to_owned()), format!("The Item {} exists already", name)));
+                        }
+
+                        Some(item.id)
+                    },
+                    Ok(MultiOption::Many(items)) => return Err(Items::ambiguous(items)),
+                    Err(e) => return Err(e)
+                }
+            },
+            _ => None
+        };
+
+        Ok((parent, base_file_id))
+    }
+
+    /// Upload `size` bytes read from `reader` into the folder identified by `parent` as the
+    /// file `name` and return the item created.
+    pub fn upload_from_reader<R: Read, C>(&self, parent: Path, name: String, reader: &mut R, size: u64, unzip: bool, conflict: C) -> Result<Item> where C: Into<UploadConflict> {
+        let mut content = match self.upload(parent, name, size, unzip, conflict) {
+            Ok(content) => content,
+            Err(e) => return Err(e)
+        };
+
+        // Copy the data and finish the upload
+        match io::copy(&mut reader.take(size), &mut content) {
+            Ok(_) => content.finish(),
+            Err(err) => Err(Error::from(err))
+        }
+    }
+
+    /// Upload `data` into the folder identified by `parent` as the file `name` and return
+    /// the item created. The data is uploaded chunk by chunk like `upload_from_reader()` does.
+    pub fn upload_bytes<C>(&self, parent: Path, name: String, data: &[u8], conflict: C) -> Result<Item> where C: Into<UploadConflict> {
+        let mut reader = data;
+        self.upload_from_reader(parent, name, &mut reader, data.len() as u64, false, conflict)
+    }
+
+    /// Upload `size` bytes read from `reader` into the folder identified by `parent` with
+    /// up to `concurrency` data chunks uploaded at the same time. Failed chunks are retried
+    /// and the upload is finished only after all chunks are uploaded successfully.
+    /// `conflict` tells what to do if the file `name` exists in the folder already,
+    /// see `upload()`.
+    pub fn upload_parallel<R: Read, C>(&self, parent: Path, name: String, reader: &mut R, size: u64, conflict: C, concurrency: usize) -> Result<()> where C: Into<UploadConflict> {
+        let conflict = conflict.into();
+        match self.upload_target(parent, &name, conflict) {
+            Ok((parent, base_file_id)) => content::upload_parallel(self.conn.clone(), parent, name, reader, size, false, conflict.overwrite(), base_file_id.as_ref().map(|id| id.as_str()), self.upload_chunk_size, concurrency),
+            Err(e) => Err(e)
+        }
+    }
+
+    // Merge the per-call `parameters` with the defaults
+    fn parameters(&self, parameters: Option<Parameters>) -> Option<Parameters> {
+        match (self.defaults.clone(), parameters) {
+            (Some(defaults), Some(parameters)) => Some(defaults.merge(parameters)),
+            (Some(defaults), None) => Some(defaults),
+            (None, parameters) => parameters,
+        }
+    }
+
+    // Construct the error listing IDs of items found where only one is expected
+    fn ambiguous(items: Vec<Item>) -> Error {
+        Error::Ambiguous(items.into_iter().map(|item| item.id).collect())
+    }
+
+    // Extract the only item from the API response
+    fn single_item(items: MultiOption<Item>) -> Result<Item> {
+        match items {
+            MultiOption::One(item) => Ok(item),
+            MultiOption::Many(items) => Err(Items::ambiguous(items)),
+            MultiOption::None => Err(Error::from("The Item is not found")),
         }
     }
 
@@ -276,4 +955,1350 @@ impl Items {
         self.conn.query_json(Method::Get, uri, None, None)
             .and_then(|data| Item::from_value(data, self.meta))
     }
+
+    // Get the items collection following `odata.nextLink` until the last page is fetched
+    fn get_all_items(&self, uri: String) -> Result<MultiOption<Item>> {
+        let mut items = Vec::new();
+        let mut fetched = BTreeSet::new();
+        let mut next = Some(uri);
+
+        while let Some(uri) = next {
+            // Guard against the next link pointing to the page fetched already
+            if !fetched.insert(uri.clone()) {
+                return Err(Error::from(format!("The next page link {} repeats the page fetched already", uri)));
+            }
+
+            let data = match self.conn.query_json(Method::Get, uri, None, None) {
+                Ok(data) => data,
+                Err(e) => return Err(e)
+            };
+
+            // The next link is absolute so make it relative to the endpoint
+            next = match Item::next_link_from_value(&data) {
+                Some(link) => match Items::relative_link(self.conn.endpoint(), &link) {
+                    Ok(uri) => Some(uri),
+                    Err(e) => return Err(e)
+                },
+                None => None
+            };
+
+            match Item::from_value(data, self.meta) {
+                Ok(page) => items.extend(page),
+                Err(e) => return Err(e)
+            };
+        }
+
+        if items.is_empty() {
+            Ok(MultiOption::None)
+        }
+        else {
+            Ok(MultiOption::Many(items))
+        }
+    }
+
+    // Make the `link` relative to the API `endpoint`. The link can be absolute or relative
+    // to the endpoint. The links pointing outside of the endpoint, e.g. to another host,
+    // are not followed.
+    fn relative_link(endpoint: &str, link: &str) -> Result<String> {
+        let endpoint = match Url::parse(endpoint) {
+            Ok(endpoint) => endpoint,
+            Err(err) => return Error::url_result(err)
+        };
+
+        let url = match endpoint.join(link) {
+            Ok(url) => url,
+            Err(err) => return Err(Error::from(err).context("The next page link is not valid"))
+        };
+
+        let same_origin = url.scheme() == endpoint.scheme()
+            && url.host_str() == endpoint.host_str()
+            && url.port_or_known_default() == endpoint.port_or_known_default();
+
+        if same_origin && url.path().starts_with(endpoint.path()) {
+            Ok(String::from(&url[Position::BeforePath..][endpoint.path().len()..]))
+        }
+        else {
+            Err(Error::from(format!("The next page link {} is outside of the API endpoint {}", link, endpoint)))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ::connection::{Connection, ConnectionSettings};
+    use ::mock::{MockServer, MockResponse};
+    use ::api::MultiOption;
+    use ::odata::Parameters;
+    use super::{Items, Path, UploadConflict};
+
+    fn items(server: &MockServer) -> Items {
+        Items::new(Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url()))
+    }
+
+    fn item_json(kind: &str, id: &str, name: &str) -> String {
+        format!("{{\"odata.type\":\"ShareFile.Api.Models.{}\",\"Id\":\"{}\",\"url\":\"https://tenant.sf-api.com/sf/v3/Items({})\",\"Name\":\"{}\",\"FileName\":\"{}\",\"Description\":\"\",\"FileSizeBytes\":1024,\"CreationDate\":\"2018-10-01T10:20:30.000Z\"}}", kind, id, id, name, name)
+    }
+
+    #[test]
+    fn parent_of_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-parent", "Documents"))]);
+
+        let parent = items(&server).parent(Path::Id("fi-child".to_owned())).unwrap();
+        assert_eq!(parent.id, "fo-parent");
+        assert_eq!(parent.name, "Documents");
+        assert!(parent.kind.is_folder());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert!(requests[0].uri.contains("Items(fi-child)/Parent"));
+    }
+
+    #[test]
+    fn parent_of_home() {
+        let server = MockServer::start(vec![]);
+        assert!(items(&server).parent(Path::Home).is_err());
+        assert_eq!(server.requests().len(), 0);
+    }
+
+    #[test]
+    fn copy_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-copy", "Documents"))]);
+
+        let path = items(&server).copy(Path::Id("fo-source".to_owned()), Path::Id("fo-target".to_owned()), true).unwrap();
+        assert_eq!(path, Path::Id("fo-copy".to_owned()));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Items(fo-source)/Copy?targetid=fo-target&overwrite=true");
+    }
+
+    #[test]
+    fn move_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-moved", "report.pdf"))]);
+
+        let item = items(&server).move_item(Path::Id("fi-moved".to_owned()), Path::Id("fo-target".to_owned()), false).unwrap();
+        assert_eq!(item.id, "fi-moved");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].uri, "/Items(fi-moved)?overwrite=false");
+        assert_eq!(requests[0].body_string(), "{\"Parent\":{\"Id\":\"fo-target\"}}");
+    }
+
+    #[test]
+    fn rename_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-renamed", "new.txt"))]);
+
+        let item = items(&server).rename(Path::Id("fi-renamed".to_owned()), "new.txt".to_owned()).unwrap();
+        assert_eq!(item.name, "new.txt");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].uri, "/Items(fi-renamed)");
+        assert_eq!(requests[0].body_string(), "{\"Name\":\"new.txt\"}");
+    }
+
+    #[test]
+    fn update_item() {
+        use std::collections::BTreeMap;
+        use serde_json::Value;
+
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-updated", "a.txt"))]);
+
+        let mut changes = BTreeMap::new();
+        changes.insert("Description".to_owned(), Value::String("Quarterly report".to_owned()));
+
+        let item = items(&server).update(Path::Id("fi-updated".to_owned()), changes).unwrap();
+        assert_eq!(item.id, "fi-updated");
+        assert_eq!(server.requests()[0].body_string(), "{\"Description\":\"Quarterly report\"}");
+    }
+
+    #[test]
+    fn update_item_without_changes() {
+        use std::collections::BTreeMap;
+
+        let server = MockServer::start(vec![]);
+        assert!(items(&server).update(Path::Id("fi-updated".to_owned()), BTreeMap::new()).is_err());
+        assert_eq!(server.requests().len(), 0);
+    }
+
+    #[test]
+    fn download_range() {
+        use std::io::Read;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(206, "0123456789")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-big\"}}", storage.url()))]);
+
+        let mut content = items(&server).download_range(Path::Id("fi-big".to_owned()), 10, Some(19)).unwrap();
+        let mut data = String::new();
+        content.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "0123456789");
+
+        assert_eq!(server.requests()[0].uri, "/Items(fi-big)/Download?redirect=false");
+        assert_eq!(storage.requests()[0].header("Range"), Some("bytes=10-19"));
+    }
+
+    #[test]
+    fn download_range_to_end() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(206, "")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-big\"}}", storage.url()))]);
+
+        items(&server).download_range(Path::Id("fi-big".to_owned()), 100, None).unwrap();
+        assert_eq!(storage.requests()[0].header("Range"), Some("bytes=100-"));
+    }
+
+    #[test]
+    fn download_to_writer() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "file content")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-1\"}}", storage.url()))]);
+
+        let mut out: Vec<u8> = Vec::new();
+        let copied = items(&server).download_to_writer(Path::Id("fi-1".to_owned()), &mut out).unwrap();
+        assert_eq!(copied, 12);
+        assert_eq!(out, b"file content".to_vec());
+    }
+
+    #[test]
+    fn download_lines() {
+        use std::io::BufRead;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "first\nsecond\nthird")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-1\"}}", storage.url()))]);
+
+        let content = items(&server).download(Path::Id("fi-1".to_owned())).unwrap();
+        let lines: Vec<String> = content.lines().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn download_zip() {
+        use std::io::Read;
+
+        let zip = vec![0x50, 0x4b, 0x03, 0x04, 0x14, 0x00];
+        let storage = MockServer::start(vec![
+            MockResponse::new(202, ""),
+            MockResponse::new(200, ""),
+            MockResponse::bytes(200, zip.clone())]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, children_json(vec![item_json("File", "fi-1", "a.txt"), item_json("File", "fi-2", "b.txt")])),
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{0}download?id=zip-1\",\"DownloadPrepStatusURL\":\"{0}status?id=zip-1\"}}", storage.url()))]);
+
+        let mut content = items(&server).download_zip(Path::Id("fo-1".to_owned())).unwrap();
+        let mut data = Vec::new();
+        content.read_to_end(&mut data).unwrap();
+        assert_eq!(data, zip);
+
+        let requests = server.requests();
+        assert_eq!(requests[2].uri, "/Items(fo-1)/BulkDownload?ids=fi-1%2Cfi-2&redirect=false");
+
+        let requests = storage.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].uri, "/status?id=zip-1");
+        assert_eq!(requests[1].uri, "/status?id=zip-1");
+        assert_eq!(requests[2].uri, "/download?id=zip-1");
+    }
+
+    #[test]
+    fn download_zip_not_folder() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt"))]);
+
+        assert!(items(&server).download_zip(Path::Id("fi-1".to_owned())).is_err());
+    }
+
+    #[test]
+    fn download_to_writer_failed() {
+        let server = MockServer::start(vec![
+            MockResponse::new(500, "")]);
+
+        let mut out: Vec<u8> = Vec::new();
+        assert!(items(&server).download_to_writer(Path::Id("fi-1".to_owned()), &mut out).is_err());
+        assert!(out.is_empty());
+    }
+
+    fn upload_spec(storage: &MockServer) -> MockResponse {
+        MockResponse::new(200, format!("{{\"ChunkUri\":\"{}upload-streaming.aspx?uploadid=up-1\"}}", storage.url()))
+    }
+
+    #[test]
+    fn upload_from_reader() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "{\"error\":false,\"value\":[{\"uploadid\":\"up-1\",\"parentid\":\"fo-target\",\"id\":\"fi-new\",\"filename\":\"a.txt\",\"size\":11}]}")]);
+        let server = MockServer::start(vec![
+            upload_spec(&storage),
+            MockResponse::new(200, item_json("File", "fi-new", "a.txt"))]);
+
+        let mut source: &[u8] = b"hello world";
+        let item = items(&server).upload_from_reader(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 11, false, true).unwrap();
+        assert_eq!(item.id, "fi-new");
+        assert_eq!(item.name, "a.txt");
+
+        assert_eq!(storage.requests()[0].body_string(), "hello world");
+        assert!(storage.requests()[0].uri.contains("finish=true"));
+        assert_eq!(server.requests()[1].uri, "/Items(fi-new)");
+    }
+
+    #[test]
+    fn upload_bytes() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "{\"error\":false,\"value\":[{\"uploadid\":\"up-1\",\"id\":\"fi-report\",\"filename\":\"report.csv\",\"size\":10}]}")]);
+        let server = MockServer::start(vec![
+            upload_spec(&storage),
+            MockResponse::new(200, item_json("File", "fi-report", "report.csv"))]);
+
+        let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+        let mut items = Items::new(conn);
+        items.set_upload_chunk_size(Some(4));
+
+        let item = items.upload_bytes(Path::Id("fo-target".to_owned()), "report.csv".to_owned(), b"a,b\n1,2\n3,", true).unwrap();
+        assert_eq!(item.id, "fi-report");
+
+        let requests = storage.requests();
+        assert_eq!(requests.iter().map(|r| r.body_string()).collect::<Vec<_>>(), vec!["a,b\n", "1,2\n", "3,"]);
+        assert!(requests[2].uri.contains("finish=true"));
+        assert!(server.requests()[0].uri.contains("fileSize=10"));
+    }
+
+    #[test]
+    fn upload_conflict_modes() {
+        let storage = MockServer::start(vec![]);
+        let server = MockServer::start(vec![
+            upload_spec(&storage),
+            upload_spec(&storage),
+            MockResponse::new(404, ""),
+            upload_spec(&storage),
+            MockResponse::new(200, item_json("File", "fi-old", "a.txt")),
+            upload_spec(&storage)]);
+
+        let items = items(&server);
+        let parent = || Path::Id("fo-target".to_owned());
+        for conflict in vec![UploadConflict::Overwrite, UploadConflict::Rename, UploadConflict::Skip, UploadConflict::NewVersion] {
+            items.upload(parent(), "a.txt".to_owned(), 1, false, conflict).unwrap();
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 6);
+        assert!(requests[0].uri.contains("overwrite=true"));
+        assert!(!requests[0].uri.contains("baseFileId"));
+        assert!(requests[1].uri.contains("overwrite=false"));
+        assert_eq!(requests[2].uri, "/Items(fo-target)/ByPath?path=a.txt");
+        assert!(requests[3].uri.contains("overwrite=false"));
+        assert!(!requests[3].uri.contains("baseFileId"));
+        assert_eq!(requests[4].uri, "/Items(fo-target)/ByPath?path=a.txt");
+        assert!(requests[5].uri.contains("overwrite=true"));
+        assert!(requests[5].uri.contains("baseFileId=fi-old"));
+    }
+
+    #[test]
+    fn upload_conflict_skip_existing() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-old", "a.txt")),
+            MockResponse::new(200, item_json("File", "fi-old", "a.txt"))]);
+
+        let items = items(&server);
+        let err = items.upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 1, false, UploadConflict::Skip).err().unwrap();
+        assert!(err.is_conflict());
+        assert_eq!(server.requests().len(), 1);
+
+        // The bool keeps meaning overwrite or not
+        let mut source: &[u8] = b"a";
+        assert!(items.upload_from_reader(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 1, false, UploadConflict::from(false)).is_err());
+        assert!(server.requests()[1].uri.contains("overwrite=false"));
+    }
+
+    #[test]
+    fn upload_content_finish() {
+        use std::io::Write;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "{\"error\":false,\"value\":[{\"uploadid\":\"up-1\",\"id\":\"fi-finished\",\"filename\":\"b.txt\"}]}")]);
+        let server = MockServer::start(vec![
+            upload_spec(&storage),
+            MockResponse::new(200, item_json("File", "fi-finished", "b.txt"))]);
+
+        // Declare more bytes than written so the upload is finished with finish()
+        let mut content = items(&server).upload(Path::Id("fo-target".to_owned()), "b.txt".to_owned(), 100, false, true).unwrap();
+        content.write_all(b"partial").unwrap();
+
+        let item = content.finish().unwrap();
+        assert_eq!(item.id, "fi-finished");
+        assert!(storage.requests()[0].uri.contains("finish=true"));
+        assert_eq!(server.requests()[1].uri, "/Items(fi-finished)");
+    }
+
+    #[test]
+    fn upload_from_reader_error_response() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "{\"error\":true,\"errorMessage\":\"Disk quota exceeded\",\"errorCode\":507}")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut source: &[u8] = b"hello world";
+        let err = items(&server).upload_from_reader(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 11, false, true).unwrap_err();
+        assert!(format!("{}", err).contains("Disk quota exceeded"));
+    }
+
+    #[test]
+    fn upload_chunk_forbidden() {
+        use std::io::Write;
+        use hyper::status::StatusCode;
+        use ::error::Error;
+
+        let forbidden = || MockResponse::new(403, "{\"code\":\"Forbidden\",\"message\":{\"lang\":\"en-US\",\"value\":\"Upload is not allowed\"},\"reason\":\"Forbidden\"}");
+        let storage = MockServer::start(vec![forbidden(), forbidden()]);
+        let server = MockServer::start(vec![upload_spec(&storage), upload_spec(&storage)]);
+
+        // The real error is wrapped into the IO error of the write
+        let mut content = items(&server).upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 11, false, true).unwrap();
+        let err = content.write_all(b"hello world").unwrap_err();
+        let inner = err.get_ref().and_then(|inner| inner.downcast_ref::<Error>()).unwrap();
+        assert_eq!(inner.status(), Some(StatusCode::Forbidden));
+
+        // And recovered when the IO error is converted back
+        let mut source: &[u8] = b"hello world";
+        let err = items(&server).upload_from_reader(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 11, false, true).unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::Forbidden));
+        assert_eq!(err.to_string(), "Forbidden: Upload is not allowed");
+    }
+
+    fn verified_items(server: &MockServer) -> Items {
+        let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+        let mut items = Items::new(conn);
+        items.set_upload_chunk_size(Some(4));
+        items.set_verify_upload(true);
+        items
+    }
+
+    #[test]
+    fn upload_verified() {
+        use std::io::Write;
+
+        // MD5 of "hello world"
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "{\"error\":false,\"value\":[{\"id\":\"fi-new\",\"md5\":\"5EB63BBBE01EEED093CB22BB8F5ACDC3\"}]}")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut content = verified_items(&server).upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 11, false, true).unwrap();
+        content.write_all(b"hello world").unwrap();
+        content.flush().unwrap();
+
+        let requests = storage.requests();
+        assert!(requests[0].uri.contains(&format!("filehash={:x}", ::md5::compute(b"hell"))));
+        assert!(requests[2].uri.contains("filehash=5eb63bbbe01eeed093cb22bb8f5acdc3"));
+    }
+
+    #[test]
+    fn upload_verified_mismatch() {
+        use std::io::Write;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "{\"error\":false,\"value\":[{\"id\":\"fi-new\",\"md5\":\"00000000000000000000000000000000\"}]}")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut content = verified_items(&server).upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 11, false, true).unwrap();
+        content.write_all(b"hello wo").unwrap();
+        let err = content.write_all(b"rld").unwrap_err();
+        assert_eq!(err.kind(), ::std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn upload_finished_on_flush() {
+        use std::io::Write;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        // Declare more bytes than actually written
+        let mut content = items(&server).upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 10, false, true).unwrap();
+        content.write_all(b"hello").unwrap();
+        assert_eq!(storage.requests().len(), 0);
+
+        content.flush().unwrap();
+
+        let requests = storage.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].uri.contains("finish=true"));
+        assert_eq!(requests[0].body_string(), "hello");
+
+        // Flushing the finished upload sends nothing
+        content.flush().unwrap();
+        assert!(content.write(b"more").is_err());
+    }
+
+    #[test]
+    fn upload_finished_on_size() {
+        use std::io::Write;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut content = items(&server).upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 5, false, true).unwrap();
+        content.write_all(b"hello").unwrap();
+        content.flush().unwrap();
+
+        let requests = storage.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].uri.contains("finish=true"));
+    }
+
+    #[test]
+    fn upload_progress() {
+        use std::io::Write;
+        use std::rc::Rc;
+        use std::cell::RefCell;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(500, "Failed")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+        let items = Items::configured(conn, false, Some(4));
+        let mut content = items.upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 10, false, true).unwrap();
+
+        let ticks = Rc::new(RefCell::new(Vec::new()));
+        let recorded = ticks.clone();
+        content.set_progress_callback(Box::new(move |written, total| recorded.borrow_mut().push((written, total))));
+
+        content.write_all(b"0123").unwrap();
+        assert_eq!(content.progress(), (4, 10));
+        content.write_all(b"4567").unwrap();
+        assert!(content.write_all(b"89").is_err());
+
+        assert_eq!(*ticks.borrow(), vec![(4, 10), (8, 10)]);
+        assert_eq!(content.progress(), (8, 10));
+    }
+
+    #[test]
+    fn download_length() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "hello")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-1\"}}", storage.url()))]);
+
+        let content = items(&server).download(Path::Id("fi-1".to_owned())).unwrap();
+        assert_eq!(content.len(), Some(5));
+    }
+
+    #[test]
+    fn download_length_unknown() {
+        use std::io::Read;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "hello").without_length()]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-1\"}}", storage.url()))]);
+
+        let mut content = items(&server).download(Path::Id("fi-1".to_owned())).unwrap();
+        assert_eq!(content.len(), None);
+
+        let mut data = String::new();
+        content.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "hello");
+    }
+
+    #[test]
+    fn upload_chunk_size() {
+        use std::io::Write;
+
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+        let mut items = Items::new(conn);
+        items.set_upload_chunk_size(Some(4));
+
+        let mut content = items.upload(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), 10, false, true).unwrap();
+        content.write_all(b"0123456789").unwrap();
+        content.flush().unwrap();
+
+        let requests = storage.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests.iter().map(|r| r.body_string()).collect::<Vec<_>>(), vec!["0123", "4567", "89"]);
+        assert!(requests[0].uri.contains("offset=0"));
+        assert!(requests[1].uri.contains("offset=4"));
+        assert!(requests[2].uri.contains("offset=8"));
+        assert!(!requests[1].uri.contains("finish=true"));
+        assert!(requests[2].uri.contains("finish=true"));
+    }
+
+    fn parallel_items(server: &MockServer) -> Items {
+        let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+        let mut items = Items::new(conn);
+        items.set_upload_chunk_size(Some(4));
+        items
+    }
+
+    #[test]
+    fn upload_parallel() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut source: &[u8] = b"0123456789";
+        parallel_items(&server).upload_parallel(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 10, true, 2).unwrap();
+
+        let mut requests = storage.requests();
+        assert_eq!(requests.len(), 3);
+
+        // The last chunk finishes the upload after others completed in any order
+        let last = requests.pop().unwrap();
+        assert_eq!(last.body_string(), "89");
+        assert!(last.uri.contains("finish=true"));
+        assert!(last.uri.contains("index=2"));
+        assert!(last.uri.contains("offset=8"));
+
+        requests.sort_by_key(|r| r.body_string());
+        assert_eq!(requests.iter().map(|r| r.body_string()).collect::<Vec<_>>(), vec!["0123", "4567"]);
+        assert!(requests[0].uri.contains("index=0") && requests[0].uri.contains("offset=0"));
+        assert!(requests[1].uri.contains("index=1") && requests[1].uri.contains("offset=4"));
+        assert!(requests.iter().all(|r| !r.uri.contains("finish=true")));
+    }
+
+    #[test]
+    fn upload_parallel_conflict() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(200, "OK")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-old", "a.txt")),
+            MockResponse::new(200, item_json("File", "fi-old", "a.txt")),
+            upload_spec(&storage)]);
+
+        let items = parallel_items(&server);
+        let mut source: &[u8] = b"01";
+        let err = items.upload_parallel(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 2, UploadConflict::Skip, 2).err().unwrap();
+        assert!(err.is_conflict());
+
+        let mut source: &[u8] = b"01";
+        items.upload_parallel(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 2, UploadConflict::NewVersion, 2).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].uri.contains("overwrite=true"));
+        assert!(requests[2].uri.contains("baseFileId=fi-old"));
+        assert_eq!(storage.requests()[0].body_string(), "01");
+    }
+
+    #[test]
+    fn upload_parallel_retries_failed_chunk() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(500, "Error"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK"),
+            MockResponse::new(200, "OK")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut source: &[u8] = b"0123456789";
+        parallel_items(&server).upload_parallel(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 10, true, 2).unwrap();
+
+        let requests = storage.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests.iter().filter(|r| r.uri.contains("finish=true")).count(), 1);
+        assert!(requests[3].uri.contains("finish=true"));
+    }
+
+    #[test]
+    fn upload_parallel_not_finished_on_failure() {
+        let storage = MockServer::start(vec![
+            MockResponse::new(500, "Error"),
+            MockResponse::new(500, "Error"),
+            MockResponse::new(500, "Error")]);
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        let mut source: &[u8] = b"01234567";
+        let result = parallel_items(&server).upload_parallel(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 8, true, 1);
+        assert!(result.is_err());
+
+        let requests = storage.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| !r.uri.contains("finish=true")));
+    }
+
+    #[test]
+    fn upload_parallel_all_chunks_failed() {
+        let storage = MockServer::start((0..6).map(|_| MockResponse::new(500, "Error")).collect());
+        let server = MockServer::start(vec![upload_spec(&storage)]);
+
+        // Many more chunks than the workers and the queue can take
+        let data = vec![b'x'; 80];
+        let mut source: &[u8] = &data;
+        let result = parallel_items(&server).upload_parallel(Path::Id("fo-target".to_owned()), "a.txt".to_owned(), &mut source, 80, true, 2);
+        assert!(result.is_err());
+        assert!(storage.requests().iter().all(|r| !r.uri.contains("finish=true")));
+    }
+
+    fn children_json(children: Vec<String>) -> String {
+        format!("{{\"odata.count\":{},\"value\":[{}]}}", children.len(), children.join(","))
+    }
+
+    #[test]
+    fn list_follows_next_link() {
+        let server = MockServer::start_with(|url| vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, format!("{{\"odata.count\":3,\"odata.nextLink\":\"{}Items(fo-1)/Children?$skip=2\",\"value\":[{},{}]}}",
+                url, item_json("File", "fi-1", "a.txt"), item_json("File", "fi-2", "b.txt"))),
+            MockResponse::new(200, children_json(vec![item_json("File", "fi-3", "c.txt")]))]);
+
+        let list = items(&server).list(Path::Id("fo-1".to_owned()), None).unwrap();
+        let ids: Vec<String> = list.into_iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec!["fi-1", "fi-2", "fi-3"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].uri, "/Items(fo-1)/Children");
+        assert_eq!(requests[2].uri, "/Items(fo-1)/Children?$skip=2");
+    }
+
+    fn tree_responses() -> Vec<MockResponse> {
+        vec![
+            MockResponse::new(200, item_json("Folder", "fo-root", "Backup")),
+            MockResponse::new(200, children_json(vec![
+                item_json("Folder", "fo-docs", "Documents"),
+                item_json("File", "fi-readme", "readme.txt")])),
+            MockResponse::new(200, children_json(vec![
+                item_json("File", "fi-report", "report.pdf"),
+                item_json("Folder", "fo-root", "Backup")]))]
+    }
+
+    #[test]
+    fn list_next_link_other_host() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"odata.nextLink\":\"https://other.sf-api.com/sf/v3/Items(fo-1)/Children?$skip=1\",\"value\":[{}]}}",
+                item_json("File", "fi-1", "a.txt")))]);
+
+        let err = items(&server).list(Path::Id("fo-1".to_owned()), None).err().unwrap();
+        assert!(err.to_string().contains("outside of the API endpoint"));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn list_next_link_relative() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"odata.nextLink\":\"Items(fo-1)/Children?$skip=1\",\"value\":[{}]}}",
+                item_json("File", "fi-1", "a.txt"))),
+            MockResponse::new(200, children_json(vec![item_json("File", "fi-2", "b.txt")]))]);
+
+        let list = items(&server).list(Path::Id("fo-1".to_owned()), None).unwrap();
+        assert_eq!(list.into_iter().map(|item| item.id).collect::<Vec<_>>(), vec!["fi-1", "fi-2"]);
+        assert_eq!(server.requests()[2].uri, "/Items(fo-1)/Children?$skip=1");
+    }
+
+    #[test]
+    fn list_next_link_same_page() {
+        let server = MockServer::start_with(|url| vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"odata.nextLink\":\"{}Items(fo-1)/Children\",\"value\":[{}]}}",
+                url, item_json("File", "fi-1", "a.txt")))]);
+
+        let err = items(&server).list(Path::Id("fo-1".to_owned()), None).err().unwrap();
+        assert!(err.to_string().contains("repeats the page"));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn list_recursive() {
+        let server = MockServer::start(tree_responses());
+
+        let found = items(&server).list_recursive(Path::Id("fo-root".to_owned()), None, None).unwrap();
+        assert_eq!(found.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["fo-docs", "fi-readme", "fi-report"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].uri.starts_with("/Items(fo-root)/Children"));
+        assert!(requests[2].uri.starts_with("/Items(fo-docs)/Children"));
+    }
+
+    #[test]
+    fn list_recursive_max_depth() {
+        let server = MockServer::start(tree_responses());
+
+        let found = items(&server).list_recursive(Path::Id("fo-root".to_owned()), None, Some(1)).unwrap();
+        assert_eq!(found.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["fo-docs", "fi-readme"]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn list_recursive_follows_next_link() {
+        let server = MockServer::start_with(|url| vec![
+            MockResponse::new(200, item_json("Folder", "fo-root", "Backup")),
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"odata.nextLink\":\"{}Items(fo-root)/Children?$skip=1\",\"value\":[{}]}}",
+                url, item_json("File", "fi-1", "a.txt"))),
+            MockResponse::new(200, children_json(vec![item_json("Folder", "fo-docs", "Documents")])),
+            MockResponse::new(200, children_json(vec![item_json("File", "fi-2", "b.txt")]))]);
+
+        let found = items(&server).list_recursive(Path::Id("fo-root".to_owned()), None, None).unwrap();
+        assert_eq!(found.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["fi-1", "fo-docs", "fi-2"]);
+
+        let requests = server.requests();
+        assert_eq!(requests[2].uri, "/Items(fo-root)/Children?$skip=1");
+        assert!(requests[3].uri.starts_with("/Items(fo-docs)/Children"));
+    }
+
+    #[test]
+    fn list_paged() {
+        let server = MockServer::start_with(|url| vec![
+            MockResponse::new(200, children_json(vec![
+                item_json("File", "fi-1", "1.txt"),
+                item_json("File", "fi-2", "2.txt")])),
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"odata.nextLink\":\"{}Items(fo-big)/Children?$skiptoken=fi-4\",\"value\":[{},{}]}}",
+                url, item_json("File", "fi-3", "3.txt"), item_json("File", "fi-4", "4.txt"))),
+            MockResponse::new(200, children_json(vec![
+                item_json("File", "fi-5", "5.txt")]))]);
+
+        let pages: Vec<Vec<super::Item>> = items(&server).list_paged(Path::Id("fo-big".to_owned()), 2)
+            .map(|page| page.unwrap())
+            .collect();
+
+        assert_eq!(pages.iter().map(|p| p.iter().map(|i| i.id.as_str()).collect::<Vec<_>>()).collect::<Vec<_>>(),
+            vec![vec!["fi-1", "fi-2"], vec!["fi-3", "fi-4"], vec!["fi-5"]]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].uri, "/Items(fo-big)/Children?%24top=2&%24skip=0");
+        assert_eq!(requests[1].uri, "/Items(fo-big)/Children?%24top=2&%24skip=2");
+        assert_eq!(requests[2].uri, "/Items(fo-big)/Children?$skiptoken=fi-4");
+    }
+
+    #[test]
+    fn list_paged_next_link_relative() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"odata.nextLink\":\"Items(fo-big)/Children?$skiptoken=fi-2\",\"value\":[{},{}]}}",
+                item_json("File", "fi-1", "1.txt"), item_json("File", "fi-2", "2.txt"))),
+            MockResponse::new(200, format!("{{\"odata.count\":1,\"odata.nextLink\":\"https://other.sf-api.com/sf/v3/Items(fo-big)/Children\",\"value\":[{}]}}",
+                item_json("File", "fi-3", "3.txt")))]);
+
+        let mut pages = items(&server).list_paged(Path::Id("fo-big".to_owned()), 2);
+        assert_eq!(pages.next().unwrap().unwrap().len(), 2);
+        assert!(pages.next().unwrap().is_err());
+        assert!(pages.next().is_none());
+        assert_eq!(server.requests()[1].uri, "/Items(fo-big)/Children?$skiptoken=fi-2");
+    }
+
+    #[test]
+    fn list_paged_stops_on_error() {
+        let server = MockServer::start(vec![
+            MockResponse::new(500, "")]);
+
+        let mut pages = items(&server).list_paged(Path::Id("fo-big".to_owned()), 2);
+        assert!(pages.next().unwrap().is_err());
+        assert!(pages.next().is_none());
+    }
+
+    #[test]
+    fn list_children_without_file_name() {
+        let folder = "{\"odata.type\":\"ShareFile.Api.Models.Folder\",\"Id\":\"fo-sub\",\"url\":\"https://tenant.sf-api.com/sf/v3/Items(fo-sub)\",\"Name\":\"Sub\",\"FileSizeBytes\":0,\"CreationDate\":\"2018-10-01T10:20:30.000Z\"}";
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-docs", "Documents")),
+            MockResponse::new(200, children_json(vec![ folder.to_owned(), item_json("File", "fi-1", "a.txt") ]))]);
+
+        match items(&server).list(Path::Id("fo-docs".to_owned()), None).unwrap() {
+            MultiOption::Many(list) => {
+                assert_eq!(list.len(), 2);
+                assert_eq!(list[0].name, "Sub");
+                assert_eq!(list[0].filename, "");
+                assert_eq!(list[0].description, "");
+                assert_eq!(list[1].filename, "a.txt");
+            },
+            other => panic!("Unexpected {:?}", other)
+        };
+    }
+
+    #[test]
+    fn list_counted() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-big", "Archive")),
+            MockResponse::new(200, format!("{{\"odata.count\":50000,\"value\":[{}]}}", item_json("File", "fi-1", "1.txt")))]);
+
+        let (found, count) = items(&server).list_counted(Path::Id("fo-big".to_owned()), Some(Parameters::new().top(1u32))).unwrap();
+        assert_eq!(count, Some(50000));
+        assert!(found.is_many());
+        assert_eq!(server.requests()[1].uri, "/Items(fo-big)/Children?%24top=1&%24inlinecount=allpages");
+    }
+
+    #[test]
+    fn list_versions() {
+        let version = |id: &str, date: &str| format!("{{\"odata.type\":\"ShareFile.Api.Models.File\",\"Id\":\"{}\",\"url\":\"https://tenant.sf-api.com/sf/v3/Items({})\",\"Name\":\"report.pdf\",\"FileName\":\"report.pdf\",\"FileSizeBytes\":2048,\"CreationDate\":\"{}\"}}", id, id, date);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, children_json(vec![
+                version("fi-v2", "2018-10-02T08:00:00.000Z"),
+                version("fi-v1", "2018-10-01T08:00:00.000Z")]))]);
+
+        match items(&server).versions(Path::Id("fi-v2".to_owned())).unwrap() {
+            MultiOption::Many(versions) => {
+                assert_eq!(versions.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["fi-v2", "fi-v1"]);
+                assert!(versions[0].creation_date > versions[1].creation_date);
+            },
+            other => panic!("Unexpected {:?}", other)
+        };
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].uri, "/Items(fi-v2)/Versions");
+    }
+
+    #[test]
+    fn restore_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt"))]);
+
+        let item = items(&server).restore(Path::Id("fi-1".to_owned())).unwrap();
+        assert_eq!(item.id, "fi-1");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Items(fi-1)/Restore");
+    }
+
+    #[test]
+    fn empty_recycle_bin() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        items(&server).empty_recycle_bin().unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Items/RecycleBin/Empty");
+    }
+
+    #[test]
+    fn search_items() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "{\"odata.metadata\":\"https://tenant.sf-api.com/sf/v3/$metadata#ShareFile.Api.Models.SearchResults@Element\",\"odata.type\":\"ShareFile.Api.Models.SearchResults\",\"PartialResults\":false,\"TotalCount\":2,\"Results\":[{\"Rank\":1,\"Score\":0.9,\"ItemID\":\"fi-1\",\"ParentID\":\"fo-1\",\"ItemType\":\"File\",\"FileName\":\"report.pdf\",\"DisplayName\":\"report.pdf\",\"Size\":2048,\"MD5\":\"0cc175b9c0f1b6a831c399e269772661\",\"CreationDate\":\"2018-10-01T10:20:30.000Z\",\"Url\":\"https://tenant.sf-api.com/sf/v3/Items(fi-1)\"},{\"Rank\":2,\"Score\":0.5,\"ItemID\":\"fo-2\",\"ParentID\":\"fo-1\",\"ItemType\":\"Folder\",\"FileName\":\"\",\"DisplayName\":\"Reports\",\"Size\":0,\"CreationDate\":\"2018-09-01T10:20:30.000Z\",\"Url\":\"https://tenant.sf-api.com/sf/v3/Items(fo-2)\"}]}")]);
+
+        let found = items(&server).search("report", Some(Parameters::new().top(10u32).skip(20u32))).unwrap();
+        match found {
+            MultiOption::Many(list) => {
+                assert_eq!(list.len(), 2);
+                assert_eq!(list[0].id, "fi-1");
+                assert_eq!(list[0].size, 2048);
+                assert_eq!(list[0].hash, Some("0cc175b9c0f1b6a831c399e269772661".to_owned()));
+                assert_eq!(list[1].id, "fo-2");
+                assert_eq!(list[1].name, "Reports");
+                assert!(list[1].kind.is_folder());
+            },
+            other => panic!("Unexpected {:?}", other)
+        };
+
+        assert_eq!(server.requests()[0].uri, "/Items/Search?query=report&%24top=10&%24skip=20");
+    }
+
+    #[test]
+    fn download_url() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "{\"odata.type\":\"ShareFile.Api.Models.DownloadSpecification\",\"DownloadToken\":\"token\",\"PrepareXmlInfo\":\"\",\"DownloadUrl\":\"https://storage.sharefile.com/download.ashx?dt=token\"}")]);
+
+        let url = items(&server).download_url(Path::Id("fi-1".to_owned())).unwrap();
+        assert_eq!(url, "https://storage.sharefile.com/download.ashx?dt=token");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].uri, "/Items(fi-1)/Download?redirect=false");
+    }
+
+    #[test]
+    fn resolve_path_error_propagates() {
+        use hyper::status::StatusCode;
+
+        let server = MockServer::start(vec![
+            MockResponse::new(503, ""),
+            MockResponse::new(503, "")]);
+
+        let err = items(&server).mkdir(Path::Absolute("/Docs".to_owned()), "New", None, false).unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::ServiceUnavailable));
+        assert!(err.to_string().starts_with("Cannot resolve parent ID: "));
+
+        let err = items(&server).remove(Path::Absolute("/Docs/a.txt".to_owned()), false, false).unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::ServiceUnavailable));
+    }
+
+    #[test]
+    fn remove_bulk_reports_unresolved() {
+        let not_found = || MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}");
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt")),
+            not_found(),
+            MockResponse::new(204, "")]);
+
+        let missing = Path::Absolute("/Docs/missing.txt".to_owned());
+        let unresolved = items(&server).remove_bulk(
+            Path::Absolute("/Docs".to_owned()),
+            vec![Path::Absolute("/Docs/a.txt".to_owned()), missing.clone(), Path::Id("fi-2".to_owned())],
+            false,
+            false).unwrap();
+        assert_eq!(unresolved, vec![missing]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[3].method, "POST");
+        assert!(requests[3].uri.starts_with("/Items(fo-1)/BulkDelete?"));
+        assert_eq!(requests[3].body_string(), "[\"fi-1\",\"fi-2\"]");
+
+        // Other failures are not mistaken for items not found
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(503, "")]);
+
+        let result = items(&server).remove_bulk(
+            Path::Absolute("/Docs".to_owned()),
+            vec![Path::Absolute("/Docs/a.txt".to_owned()), Path::Id("fi-2".to_owned())],
+            false,
+            false);
+        assert!(result.is_err());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn list_with_defaults() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, children_json(vec![item_json("File", "fi-1", "a.txt")]))]);
+
+        let items = items(&server)
+            .with_defaults(Parameters::new().expand(vec!["Children"]).top(100u32));
+        items.list(Path::Id("fo-1".to_owned()), Some(Parameters::new().top(5u32))).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].uri, "/Items(fo-1)?%24expand=Children&%24top=100");
+        assert_eq!(requests[1].uri, "/Items(fo-1)/Children?%24expand=Children&%24top=5");
+    }
+
+    #[test]
+    fn access_controls() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, "{\"odata.count\":1,\"value\":[{\"Principal\":{\"Id\":\"u-1\",\"Email\":\"jane@example.com\"},\"CanUpload\":true,\"CanDownload\":true}]}")]);
+
+        match items(&server).access_controls(Path::Absolute("/Docs".to_owned())).unwrap() {
+            MultiOption::Many(list) => {
+                assert_eq!(list.len(), 1);
+                assert_eq!(list[0].principal_email, Some("jane@example.com".to_owned()));
+                assert!(list[0].can_upload);
+            },
+            other => panic!("Unexpected {:?}", other)
+        }
+
+        assert_eq!(server.requests()[1].uri, "/Items(fo-1)/AccessControls");
+    }
+
+    #[test]
+    fn get_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt")),
+            MockResponse::new(200, children_json(vec![item_json("File", "fi-1", "a.txt"), item_json("File", "fi-2", "b.txt")]))]);
+
+        let items = items(&server);
+        let item = items.get("fi-1", Some(Parameters::new().select_add("Id"))).unwrap();
+        assert_eq!(item.id, "fi-1");
+        assert_eq!(item.name, "a.txt");
+        assert_eq!(server.requests()[0].uri, "/Items(fi-1)?%24select=Id");
+
+        assert!(items.get("fi-1", None).is_err());
+    }
+
+    #[test]
+    fn download_ambiguous_path() {
+        let many = || MockResponse::new(200, children_json(vec![item_json("File", "fi-1", "a.txt"), item_json("File", "fi-2", "a.txt")]));
+        let server = MockServer::start(vec![many(), many()]);
+
+        let items = items(&server);
+        let err = items.download(Path::Absolute("/Docs/a.txt".to_owned())).err().unwrap();
+        assert_eq!(err.candidates(), Some(&["fi-1".to_owned(), "fi-2".to_owned()][..]));
+        assert_eq!(err.to_string(), "There are more than one Item: fi-1, fi-2");
+
+        let err = items.upload(Path::Absolute("/Docs".to_owned()), "b.txt".to_owned(), 1, false, true).err().unwrap();
+        assert_eq!(err.candidates().map(|ids| ids.len()), Some(2));
+    }
+
+    #[test]
+    fn mkdir_item() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-new", "Reports"))]);
+
+        let item = items(&server).mkdir_item(Path::Id("fo-root".to_owned()), "Reports", None, false).unwrap();
+        assert_eq!(item.id, "fo-new");
+        assert_eq!(item.name, "Reports");
+        assert!(item.is_folder());
+        assert_eq!(item.creation_date.to_rfc3339(), "2018-10-01T10:20:30+00:00");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert!(requests[0].uri.starts_with("/Items(fo-root)/Folder?"));
+        assert_eq!(requests[0].body_string(), "{\"Name\":\"Reports\"}");
+    }
+
+    #[test]
+    fn mkdir_p() {
+        let not_found = || MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}");
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("Folder", "fo-a", "a")),
+            not_found(),
+            MockResponse::new(200, "{\"Id\":\"fo-b\"}"),
+            not_found(),
+            MockResponse::new(200, "{\"Id\":\"fo-c\"}")]);
+
+        let path = items(&server).mkdir_p(Path::Id("fo-root".to_owned()), "/a/b/c", Some("Nested".to_owned())).unwrap();
+        assert_eq!(path, Path::Id("fo-c".to_owned()));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[0].uri, "/Items(fo-root)/ByPath?path=a");
+        assert_eq!(requests[1].uri, "/Items(fo-a)/ByPath?path=b");
+        assert_eq!(requests[2].method, "POST");
+        assert!(requests[2].uri.starts_with("/Items(fo-a)/Folder?"));
+        assert!(requests[2].body_string().contains("\"Name\":\"b\""));
+        assert!(requests[2].body_string().contains("\"Description\":\"Nested\""));
+        assert!(requests[4].uri.starts_with("/Items(fo-b)/Folder?"));
+    }
+
+    #[test]
+    fn mkdir_p_over_file() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-a", "a"))]);
+
+        assert!(items(&server).mkdir_p(Path::Id("fo-root".to_owned()), "a/b", None).is_err());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn exists_and_is_folder() {
+        let not_found = || MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}");
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt")),
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt")),
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            MockResponse::new(200, item_json("Folder", "fo-1", "Docs")),
+            not_found(),
+            not_found()]);
+
+        let items = items(&server);
+        let file = Path::Absolute("/a.txt".to_owned());
+        let folder = Path::Absolute("/Docs".to_owned());
+        let missing = Path::Absolute("/missing".to_owned());
+
+        assert_eq!(items.exists(file.clone()).unwrap(), true);
+        assert_eq!(items.is_folder(file).unwrap(), false);
+        assert_eq!(items.exists(folder.clone()).unwrap(), true);
+        assert_eq!(items.is_folder(folder).unwrap(), true);
+        assert_eq!(items.exists(missing.clone()).unwrap(), false);
+        assert_eq!(items.is_folder(missing).unwrap(), false);
+    }
+
+    #[test]
+    fn exists_failed() {
+        let server = MockServer::start(vec![
+            MockResponse::new(500, "")]);
+
+        assert!(items(&server).exists(Path::Absolute("/a.txt".to_owned())).is_err());
+    }
+
+    #[test]
+    fn thumbnail() {
+        let image = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0xff];
+        let storage = MockServer::start(vec![
+            MockResponse::bytes(200, image.clone()).header("Content-Type", "image/png")]);
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"odata.type\":\"ShareFile.Api.Models.Redirection\",\"Uri\":\"{}thumbnail?id=fi-1\"}}", storage.url()))]);
+
+        let data = items(&server).thumbnail(Path::Id("fi-1".to_owned()), 75).unwrap();
+        assert_eq!(data, image);
+        assert_eq!(server.requests()[0].uri, "/Items(fi-1)/Thumbnail?size=75&redirect=false");
+        assert_eq!(storage.requests()[0].uri, "/thumbnail?id=fi-1");
+    }
+
+    #[test]
+    fn thumbnail_missing() {
+        let server = MockServer::start(vec![
+            MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Thumbnail not found\"},\"reason\":\"NotFound\"}")]);
+
+        let err = items(&server).thumbnail(Path::Id("fo-1".to_owned()), 75).unwrap_err();
+        assert!(err.is_not_found());
+        assert!(err.to_string().starts_with("The Item has no thumbnail"));
+
+        // Other failures are not mistaken for the missing thumbnail
+        let server = MockServer::start(vec![
+            MockResponse::new(503, "")]);
+
+        let err = items(&server).thumbnail(Path::Id("fo-1".to_owned()), 75).unwrap_err();
+        assert!(!err.to_string().contains("no thumbnail"));
+    }
+
+    #[test]
+    fn share_link() {
+        use chrono::Duration;
+
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "{\"ShareType\":\"Send\",\"Id\":\"s-1\",\"Uri\":\"https://tenant.sharefile.com/d-s1\",\"RequireLogin\":true}")]);
+
+        let share = items(&server).share_link(Path::Id("fi-1".to_owned()), Some(Duration::days(7)), true).unwrap();
+        assert_eq!(share.id, "s-1");
+        assert_eq!(share.uri, Some("https://tenant.sharefile.com/d-s1".to_owned()));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Shares?notify=false");
+
+        let body = requests[0].body_string();
+        assert!(body.contains("\"ShareType\":\"Send\""));
+        assert!(body.contains("\"Items\":[{\"Id\":\"fi-1\"}]"));
+        assert!(body.contains("\"RequireLogin\":true"));
+        assert!(body.contains("\"ExpirationDate\""));
+    }
+
+    #[test]
+    fn add_favorite() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "{\"odata.type\":\"ShareFile.Api.Models.FavoriteFolder\",\"FolderName\":\"Docs\",\"Id\":\"fo-1\"}")]);
+
+        items(&server).add_favorite(Path::Id("fo-1".to_owned())).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Users(me)/FavoriteFolders");
+        assert_eq!(requests[0].body_string(), "{\"Item\":{\"Id\":\"fo-1\"}}");
+    }
+
+    #[test]
+    fn remove_favorite() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        items(&server).remove_favorite(Path::Id("fo-1".to_owned())).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "DELETE");
+        assert_eq!(requests[0].uri, "/Users(me)/FavoriteFolders(fo-1)");
+    }
+
+    #[test]
+    fn stat_if_changed() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt")).header("ETag", "\"v2\""),
+            MockResponse::new(304, "")]);
+
+        let (item, etag) = items(&server).stat_if_changed(Path::Id("fi-1".to_owned()), Some("\"v1\"")).unwrap().unwrap();
+        assert_eq!(item.id, "fi-1");
+        assert_eq!(etag, Some("\"v2\"".to_owned()));
+
+        let item = items(&server).stat_if_changed(Path::Id("fi-1".to_owned()), etag.as_ref().map(|etag| etag.as_str())).unwrap();
+        assert!(item.is_none());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("If-None-Match"), Some("\"v1\""));
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v2\""));
+    }
+
+    #[test]
+    fn stat_if_changed_first_check() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, item_json("File", "fi-1", "a.txt")).header("ETag", "\"v1\"")]);
+
+        let items = items(&server).with_defaults(Parameters::new().select(vec!["Id", "Name"]));
+        let (_, etag) = items.stat_if_changed(Path::Id("fi-1".to_owned()), None).unwrap().unwrap();
+        assert_eq!(etag, Some("\"v1\"".to_owned()));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("If-None-Match"), None);
+        assert!(requests[0].uri.contains("%24select=Id%2CName"));
+    }
+
+    #[test]
+    fn check_out() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        items(&server).check_out(Path::Id("fi-1".to_owned())).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Items(fi-1)/CheckOut");
+    }
+
+    #[test]
+    fn check_out_by_another_user() {
+        let server = MockServer::start(vec![
+            MockResponse::new(409, "{\"code\":\"Conflict\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item is checked out\"},\"reason\":\"Conflict\"}")]);
+
+        let err = items(&server).check_out(Path::Id("fi-1".to_owned())).unwrap_err();
+        assert!(err.is_conflict());
+        assert_eq!(err.to_string(), "The Item is already checked out by another user: Conflict: Item is checked out");
+    }
+
+    #[test]
+    fn check_in() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        items(&server).check_in(Path::Id("fi-1".to_owned()), false).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Items(fi-1)/CheckIn");
+    }
+
+    #[test]
+    fn check_in_keep_checked_out() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, ""),
+            MockResponse::new(204, "")]);
+
+        items(&server).check_in(Path::Id("fi-1".to_owned()), true).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].uri, "/Items(fi-1)/CheckIn");
+        assert_eq!(requests[1].uri, "/Items(fi-1)/CheckOut");
+    }
+
+    #[test]
+    fn discard_check_out() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        items(&server).discard_check_out(Path::Id("fi-1".to_owned())).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Items(fi-1)/DiscardCheckOut");
+    }
+
+    #[test]
+    fn stat_not_found() {
+        let server = MockServer::start(vec![
+            MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}")]);
+
+        let found = items(&server).stat(Path::Id("missing".to_owned()), None).unwrap();
+        assert_eq!(found, MultiOption::None);
+    }
 }
@@ -0,0 +1,152 @@
+use hyper::method::Method;
+use ::connection::Connection;
+use ::odata::Parameters;
+use ::api::MultiOption;
+use ::error::{Result, Error};
+use super::{Items, Item, Path};
+
+
+/// Iterator over the children of the folder listed page by page.
+///
+/// Each iteration issues one API request and yields the items of the page or the error
+/// occurred. The next page is requested following `odata.nextLink` if the server returns it,
+/// otherwise advancing `$skip` by the page size. The iteration stops when the page returned
+/// is not full or after the first error.
+pub struct ItemPages {
+	conn: Connection,
+	meta: bool,
+	path: Option<Path>,
+	folder: Option<Path>,
+	page_size: u32,
+	skip: u32,
+	next_uri: Option<String>,
+	done: bool,
+}
+
+
+impl ItemPages {
+	/// Create the new instance of `ItemPages` listing the folder at `path` by `page_size`
+	/// items. Most time there is no need to create this struct directly. The better practice
+	/// is to use method `list_paged()` of the `Items` instance.
+	pub fn new(conn: Connection, meta: bool, path: Path, page_size: u32) -> Self {
+		ItemPages {
+			conn: conn,
+			meta: meta,
+			path: Some(path),
+			folder: None,
+			page_size: page_size,
+			skip: 0,
+			next_uri: None,
+			done: false,
+		}
+	}
+
+	// Build the URI of the page starting at `skip` of the folder identified by `folder`
+	fn page_uri(&self, folder: &Path) -> String {
+		let params = Parameters::new()
+			.top(self.page_size)
+			.skip(self.skip);
+
+		folder.entity_and_parameters(Some("/Children"), Some(params))
+	}
+
+	// Resolve the folder path to the ID
+	fn resolve(&self, path: Path) -> Result<Path> {
+		if path.is_id() {
+			return Ok(path);
+		}
+
+		Items::new(self.conn.clone()).stat(path, None)
+			.and_then(Items::single_item)
+			.map(|folder| folder.path())
+	}
+
+	// Fetch the page and decide which page goes next
+	fn fetch(&mut self, uri: String) -> Result<Vec<Item>> {
+		let data = match self.conn.query_json(Method::Get, uri.clone(), None, None) {
+			Ok(data) => data,
+			Err(e) => return Err(e)
+		};
+
+		let next_link = data.find("odata.nextLink")
+			.and_then(|v| v.as_str())
+			.map(|link| link.to_owned());
+
+		let items = match Item::from_value(data, self.meta) {
+			Ok(MultiOption::Many(items)) => items,
+			Ok(MultiOption::One(item)) => vec![item],
+			Ok(MultiOption::None) => Vec::new(),
+			Err(e) => return Err(e)
+		};
+
+		match next_link {
+			Some(link) => {
+				let next_uri = match Items::relative_link(self.conn.endpoint(), &link) {
+					Ok(next_uri) => next_uri,
+					Err(e) => return Err(e)
+				};
+
+				// Guard against the next link pointing to the page fetched
+				if next_uri == uri {
+					return Err(Error::from(format!("The next page link {} repeats the page fetched already", next_uri)));
+				}
+
+				self.next_uri = Some(next_uri);
+			},
+			None => {
+				if (items.len() as u32) < self.page_size {
+					self.done = true;
+				}
+				else {
+					self.skip = self.skip + self.page_size;
+				}
+			}
+		};
+
+		Ok(items)
+	}
+}
+
+
+impl Iterator for ItemPages {
+	type Item = Result<Vec<Item>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+
+		// Resolve the folder on the first page
+		let first = self.folder.is_none();
+		if let Some(path) = self.path.take() {
+			match self.resolve(path) {
+				Ok(folder) => self.folder = Some(folder),
+				Err(e) => {
+					self.done = true;
+					return Some(Err(e));
+				}
+			};
+		}
+
+		// Find out which page to fetch
+		let uri = match self.next_uri.take() {
+			Some(uri) => uri,
+			None => match self.folder {
+				Some(ref folder) => self.page_uri(folder),
+				None => return None
+			}
+		};
+
+		match self.fetch(uri) {
+			Ok(ref items) if items.is_empty() && !first => {
+				self.done = true;
+				None
+			},
+			Ok(items) => Some(Ok(items)),
+			Err(e) => {
+				self.done = true;
+				Some(Err(e))
+			}
+		}
+	}
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+use std::str::FromStr;
 use serde_json::Value;
 use ::{Error, Result};
 use ::odata::Parameters;
@@ -130,25 +132,89 @@ impl Path {
         let segment = segment.map_or("", |s| s);
         let parameters = parameters.map_or(String::from(""), |p| p.to_string());
 
+        // Query string and query string continuation, both empty if there are no parameters
+        let (query, more) = if parameters.is_empty() {
+            (String::new(), String::new())
+        }
+        else {
+            (format!("?{}", parameters), format!("&{}", parameters))
+        };
+
         match self {
-            &Path::Home => format!("Items(home){}?{}", segment, parameters),
-            &Path::Favorites => format!("Items(favorites){}?{}", segment, parameters),
-            &Path::AllShared => format!("Items(allshared){}?{}", segment, parameters),
-            &Path::Connectors => format!("Items(connectors){}?{}", segment, parameters),
-            &Path::Box => format!("Items(box){}?{}", segment, parameters),
-            &Path::Top => format!("Items(top){}?{}", segment, parameters),
-            &Path::Id(ref id) => format!("Items({}){}?{}", id, segment, parameters),
-            &Path::Absolute(ref path) => format!("Items/ByPath?path={}&{}", path, parameters),
-            &Path::Relative(ref id, ref path) => format!("Items({})/ByPath?path={}&{}", id, path, parameters),
-            &Path::Parent(ref id) => format!("Items({})/Parent&{}", id, parameters),
+            &Path::Home => format!("Items(home){}{}", segment, query),
+            &Path::Favorites => format!("Items(favorites){}{}", segment, query),
+            &Path::AllShared => format!("Items(allshared){}{}", segment, query),
+            &Path::Connectors => format!("Items(connectors){}{}", segment, query),
+            &Path::Box => format!("Items(box){}{}", segment, query),
+            &Path::Top => format!("Items(top){}{}", segment, query),
+            &Path::Id(ref id) => format!("Items({}){}{}", id, segment, query),
+            &Path::Absolute(ref path) => format!("Items/ByPath?{}{}", Path::encode_path(path), more),
+            &Path::Relative(ref id, ref path) => format!("Items({})/ByPath?{}{}", id, Path::encode_path(path), more),
+            &Path::Parent(ref id) => format!("Items({})/Parent{}", id, query),
         }
     }
 
+    // Encode the path into `path` query parameter
+    fn encode_path(path: &str) -> String {
+        use url::form_urlencoded;
+
+        form_urlencoded::Serializer::new(String::new())
+            .append_pair("path", path)
+            .finish()
+    }
+
     /// Construct Path from the result JSON
     pub fn from_json(value: Value) -> Result<Path> {
         value.find("Id")
             .ok_or(Error::from("Cannot find Item ID"))
-            .map(|id| Path::Id(id.as_str().unwrap().to_owned()))
+            .and_then(|id| id.as_str().ok_or(Error::from("Item ID must be a string")))
+            .map(|id| Path::Id(id.to_owned()))
+    }
+}
+
+
+// Parse the path from the string like `home`, `id:fo123` or `/path/to/item`
+impl FromStr for Path {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Path> {
+        if s.starts_with('/') {
+            return Ok(Path::Absolute(s.to_owned()));
+        }
+
+        if s.len() > 3 && s.get(0..3).map_or(false, |prefix| prefix.eq_ignore_ascii_case("id:")) {
+            return Ok(Path::Id(s[3..].to_owned()));
+        }
+
+        match s.to_lowercase().as_str() {
+            "home" => Ok(Path::Home),
+            "favorites" => Ok(Path::Favorites),
+            "allshared" => Ok(Path::AllShared),
+            "connectors" => Ok(Path::Connectors),
+            "box" => Ok(Path::Box),
+            "top" => Ok(Path::Top),
+            _ => Err(Error::Other(format!("Cannot parse path {}", s)))
+        }
+    }
+}
+
+
+// Display the path in the form `FromStr` parses, relative and parent paths which cannot
+// be parsed are displayed as `id:path` and `parent:id`
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &Path::Home => write!(f, "home"),
+            &Path::Favorites => write!(f, "favorites"),
+            &Path::AllShared => write!(f, "allshared"),
+            &Path::Connectors => write!(f, "connectors"),
+            &Path::Box => write!(f, "box"),
+            &Path::Top => write!(f, "top"),
+            &Path::Id(ref id) => write!(f, "id:{}", id),
+            &Path::Absolute(ref path) => write!(f, "{}", path),
+            &Path::Relative(ref id, ref path) => write!(f, "{}:{}", id, path),
+            &Path::Parent(ref id) => write!(f, "parent:{}", id),
+        }
     }
 }
 
@@ -242,6 +308,115 @@ mod tests {
         assert!(path.is_parent());
     }
 
+    #[test]
+    fn path_from_str_aliases() {
+        assert_eq!("home".parse::<Path>().unwrap(), Path::Home);
+        assert_eq!("Favorites".parse::<Path>().unwrap(), Path::Favorites);
+        assert_eq!("allshared".parse::<Path>().unwrap(), Path::AllShared);
+        assert_eq!("connectors".parse::<Path>().unwrap(), Path::Connectors);
+        assert_eq!("BOX".parse::<Path>().unwrap(), Path::Box);
+        assert_eq!("top".parse::<Path>().unwrap(), Path::Top);
+    }
+
+    #[test]
+    fn path_from_str_id() {
+        assert_eq!("id:fo123".parse::<Path>().unwrap(), Path::Id("fo123".to_owned()));
+        assert!("id:".parse::<Path>().is_err());
+    }
+
+    #[test]
+    fn path_from_str_absolute() {
+        assert_eq!("/Folder/file.txt".parse::<Path>().unwrap(), Path::Absolute("/Folder/file.txt".to_owned()));
+    }
+
+    #[test]
+    fn path_from_str_invalid() {
+        assert!("somewhere".parse::<Path>().is_err());
+        assert!("".parse::<Path>().is_err());
+        assert!("ид:1".parse::<Path>().is_err());
+    }
+
+    #[test]
+    fn path_display_round_trip() {
+        let paths = vec![
+            Path::Home,
+            Path::Favorites,
+            Path::AllShared,
+            Path::Connectors,
+            Path::Box,
+            Path::Top,
+            Path::Id("fo123".to_owned()),
+            Path::Absolute("/Folder/file.txt".to_owned()),
+        ];
+
+        for path in paths {
+            assert_eq!(path.to_string().parse::<Path>().unwrap(), path);
+        }
+    }
+
+    #[test]
+    fn path_display() {
+        assert_eq!(Path::Home.to_string(), "home");
+        assert_eq!(Path::Id("fo123".to_owned()).to_string(), "id:fo123");
+        assert_eq!(Path::Relative("fo123".to_owned(), "sub/file.txt".to_owned()).to_string(), "fo123:sub/file.txt");
+        assert_eq!(Path::Parent("fi456".to_owned()).to_string(), "parent:fi456");
+    }
+
+    fn query_path(uri: &str) -> Option<String> {
+        use url::form_urlencoded;
+
+        let query = &uri[uri.find('?').unwrap() + 1..];
+        form_urlencoded::parse(query.as_bytes())
+            .find(|&(ref name, _)| name == "path")
+            .map(|(_, value)| value.into_owned())
+    }
+
+    #[test]
+    fn path_absolute_encoded() {
+        let path = Path::Absolute("/My Folder/a&b.txt".to_owned());
+        let uri = path.entity_and_parameters(None, None);
+        assert!(uri.starts_with("Items/ByPath?path="));
+        assert!(!uri.contains(' '));
+        assert!(!uri.contains("a&b"));
+        assert_eq!(query_path(&uri), Some("/My Folder/a&b.txt".to_owned()));
+    }
+
+    #[test]
+    fn path_relative_encoded() {
+        let path = Path::Relative("fo123".to_owned(), "Sub #1/what?.txt".to_owned());
+        let uri = path.entity_and_parameters(None, None);
+        assert!(uri.starts_with("Items(fo123)/ByPath?path="));
+        assert!(!uri.contains('#'));
+        assert_eq!(query_path(&uri), Some("Sub #1/what?.txt".to_owned()));
+    }
+
+    #[test]
+    fn path_home_query() {
+        use ::odata::Parameters;
+
+        assert_eq!(Path::Home.entity_and_parameters(None, None), "Items(home)");
+        assert_eq!(Path::Home.entity_and_parameters(Some("/Children"), Some(Parameters::new())), "Items(home)/Children");
+        assert_eq!(Path::Home.entity_and_parameters(None, Some(Parameters::new().select_add("Id"))), "Items(home)?%24select=Id");
+    }
+
+    #[test]
+    fn path_by_path_query() {
+        use ::odata::Parameters;
+
+        let path = Path::Relative("fo123".to_owned(), "a".to_owned());
+        assert_eq!(path.entity_and_parameters(None, None), "Items(fo123)/ByPath?path=a");
+        assert_eq!(path.entity_and_parameters(None, Some(Parameters::new().select_add("Id"))), "Items(fo123)/ByPath?path=a&%24select=Id");
+    }
+
+    #[test]
+    fn path_parent_query_delimiter() {
+        use ::odata::Parameters;
+
+        let path = Path::Parent("fo123".to_owned());
+        let uri = path.entity_and_parameters(None, Some(Parameters::new().select_add("Id")));
+        assert_eq!(uri, "Items(fo123)/Parent?%24select=Id");
+    }
+
     #[test]
     fn parse_valid_path() {
         let data: Value = serde_json::from_str("{\"Id\":\"fi111111-2222-3333-4444-555555555555\"}").unwrap();
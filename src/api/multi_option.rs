@@ -34,6 +34,42 @@ impl<T> MultiOption<T> {
 			_ => false
 		}
 	}
+
+	/// Convert the option into the vector of values it contains
+	pub fn into_vec(self) -> Vec<T> {
+		match self {
+			MultiOption::None => Vec::new(),
+			MultiOption::One(v) => vec![v],
+			MultiOption::Many(list) => list,
+		}
+	}
+
+	/// Get the value if the option is `One`. Returns `None` for both `None` and `Many`
+	pub fn one(self) -> Option<T> {
+		match self {
+			MultiOption::One(v) => Some(v),
+			_ => None
+		}
+	}
+
+	/// Convert each value with `f` keeping the variant of the option
+	pub fn map<U, F>(self, mut f: F) -> MultiOption<U> where F: FnMut(T) -> U {
+		match self {
+			MultiOption::None => MultiOption::None,
+			MultiOption::One(v) => MultiOption::One(f(v)),
+			MultiOption::Many(list) => MultiOption::Many(list.into_iter().map(f).collect()),
+		}
+	}
+}
+
+
+impl<T> IntoIterator for MultiOption<T> {
+	type Item = T;
+	type IntoIter = ::std::vec::IntoIter<T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.into_vec().into_iter()
+	}
 }
 
 
@@ -70,4 +106,37 @@ mod tests {
 		let opt: MultiOption<usize> = MultiOption::Many(vec![1, 2, 3]);
 		assert!(opt.is_many());
 	}
+
+	#[test]
+	fn multi_option_into_vec() {
+		assert_eq!(MultiOption::None::<usize>.into_vec(), Vec::<usize>::new());
+		assert_eq!(MultiOption::One(1).into_vec(), vec![1]);
+		assert_eq!(MultiOption::Many(vec![1, 2, 3]).into_vec(), vec![1, 2, 3]);
+	}
+
+	#[test]
+	fn multi_option_into_one() {
+		assert_eq!(MultiOption::None::<usize>.one(), None);
+		assert_eq!(MultiOption::One(1).one(), Some(1));
+		assert_eq!(MultiOption::Many(vec![1, 2, 3]).one(), None);
+	}
+
+	#[test]
+	fn multi_option_map() {
+		assert_eq!(MultiOption::None::<usize>.map(|v| v * 2), MultiOption::None);
+		assert_eq!(MultiOption::One(1).map(|v| v * 2), MultiOption::One(2));
+		assert_eq!(MultiOption::Many(vec![1, 2, 3]).map(|v| v.to_string()), MultiOption::Many(vec!["1".to_owned(), "2".to_owned(), "3".to_owned()]));
+	}
+
+	#[test]
+	fn multi_option_into_iter() {
+		assert_eq!(MultiOption::None::<usize>.into_iter().count(), 0);
+		assert_eq!(MultiOption::One(1).into_iter().collect::<Vec<_>>(), vec![1]);
+
+		let mut sum = 0;
+		for v in MultiOption::Many(vec![1, 2, 3]) {
+			sum += v;
+		}
+		assert_eq!(sum, 6);
+	}
 }
@@ -1,27 +1,179 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde_json::Value;
-use ::Result;
+use ::error::{Result, Error, IoError, IoErrorKind};
 
 
 /// Share Access Right
+///
+/// The flags tell what the recipient can do with the share. Flags missing in the API
+/// response are assumed to be denied.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub enum AccessRight {
-	/// The owner of the share have the full control over the share
-	///
-	/// The detailed information about ShareAccess Right in ShareFile REST API docs is missing
-	/// so lets assume access right is always FullControl until the more information become
-	/// available.
-	FullControl,
+pub struct AccessRight {
+	/// The recipient can view the shared items
+	pub can_view: bool,
+	/// The recipient can download the shared items
+	pub can_download: bool,
+	/// The recipient can upload items to the share
+	pub can_upload: bool,
+	/// The recipient can delete the shared items
+	pub can_delete: bool,
 }
 
 
 impl AccessRight {
-    /// Construct access right from the decoded JSON value.
-    pub fn from_json(_value: Value) -> Result<AccessRight> {
-        Ok(AccessRight::FullControl)
+    /// Create access right granting everything.
+    pub fn full_control() -> AccessRight {
+        AccessRight {
+            can_view: true,
+            can_download: true,
+            can_upload: true,
+            can_delete: true,
+        }
+    }
+
+    /// Create access right granting nothing.
+    pub fn none() -> AccessRight {
+        AccessRight {
+            can_view: false,
+            can_download: false,
+            can_upload: false,
+            can_delete: false,
+        }
+    }
+
+    /// Construct access right from the decoded JSON value. Missing and `null` flags
+    /// are `false`.
+    pub fn from_json(value: Value) -> Result<AccessRight> {
+        if let Some(object) = value.as_object() {
+            let read = |name: &str| match object.get(name) {
+                Some(&Value::Null) | None => Ok(false),
+                Some(v) => match v.as_bool() {
+                    Some(v) => Ok(v),
+                    None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.ShareAccessRight.{} property must be a boolean.", name))),
+                },
+            };
+
+            let can_view = match read("CanView") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
+
+            let can_download = match read("CanDownload") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
+
+            let can_upload = match read("CanUpload") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
+
+            let can_delete = match read("CanDelete") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
+
+            Ok(AccessRight {
+                can_view: can_view,
+                can_download: can_download,
+                can_upload: can_upload,
+                can_delete: can_delete,
+            })
+        }
+        else {
+            Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.ShareAccessRight can be constructed from JSON Object only."))
+        }
     }
 
     /// Test if access right is full control
     pub fn is_full_control(&self) -> bool {
-    	true
+        self.can_view && self.can_download && self.can_upload && self.can_delete
+    }
+
+    /// Test if access right allows viewing and downloading only
+    pub fn is_read_only(&self) -> bool {
+        !self.can_upload && !self.can_delete
+    }
+}
+
+
+// Serialize with the same property names the API uses
+impl Serialize for AccessRight {
+    fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+    where S: Serializer {
+        use serde_json::value::Map;
+        let mut object: Map<String, Value> = Map::new();
+
+        object.insert("CanView".to_owned(), Value::Bool(self.can_view));
+        object.insert("CanDownload".to_owned(), Value::Bool(self.can_download));
+        object.insert("CanUpload".to_owned(), Value::Bool(self.can_upload));
+        object.insert("CanDelete".to_owned(), Value::Bool(self.can_delete));
+
+        Value::Object(object).serialize(serializer)
+    }
+}
+
+
+// Deserialize through the JSON Value representation
+impl Deserialize for AccessRight {
+    fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<AccessRight, D::Error>
+    where D: Deserializer {
+        use serde::de::Error as DeError;
+
+        Value::deserialize(deserializer)
+            .and_then(|value| AccessRight::from_json(value)
+                .map_err(|err| D::Error::custom(err.to_string())))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::AccessRight;
+
+    #[test]
+    fn access_right_read_only() {
+        let value = serde_json::from_str("{\"CanView\":true,\"CanDownload\":true,\"CanUpload\":false,\"CanDelete\":false}").unwrap();
+        let access_right = AccessRight::from_json(value).unwrap();
+        assert!(access_right.can_view);
+        assert!(access_right.can_download);
+        assert!(!access_right.can_upload);
+        assert!(!access_right.can_delete);
+        assert!(access_right.is_read_only());
+        assert!(!access_right.is_full_control());
+    }
+
+    #[test]
+    fn access_right_full_control() {
+        let value = serde_json::from_str("{\"CanView\":true,\"CanDownload\":true,\"CanUpload\":true,\"CanDelete\":true}").unwrap();
+        let access_right = AccessRight::from_json(value).unwrap();
+        assert!(access_right.is_full_control());
+        assert_eq!(access_right, AccessRight::full_control());
+    }
+
+    #[test]
+    fn access_right_missing_flags() {
+        let value = serde_json::from_str("{}").unwrap();
+        assert_eq!(AccessRight::from_json(value).unwrap(), AccessRight::none());
+
+        let value = serde_json::from_str("{\"CanView\":true,\"CanDownload\":null}").unwrap();
+        let access_right = AccessRight::from_json(value).unwrap();
+        assert!(access_right.can_view);
+        assert!(!access_right.can_download);
+        assert!(!access_right.can_upload);
+        assert!(!access_right.can_delete);
+    }
+
+    #[test]
+    fn access_right_wrong_flag_type() {
+        let value = serde_json::from_str("{\"CanUpload\":\"true\"}").unwrap();
+        assert!(AccessRight::from_json(value).is_err());
+    }
+
+    #[test]
+    fn access_right_not_object() {
+        let value = serde_json::from_str("\"FullControl\"").unwrap();
+        assert!(AccessRight::from_json(value).is_err());
     }
 }
@@ -1,6 +1,9 @@
 use super::Kind;
 use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde_json::{self, Value};
+use serde_json::value::ToJson;
 use ::{Error, Result};
 use ::api::items::Path;
 use ::api::users::UserId;
@@ -29,6 +32,8 @@ pub struct ShareConfig {
     require_user_info: Option<bool>,
     /// Maximum number of downloads each user can perform.
     max_downloads: Option<i32>,
+    /// Subject and body of the notification e-mail message.
+    message: Option<(String, String)>,
 }
 
 
@@ -45,6 +50,7 @@ impl ShareConfig {
             require_login: None,
             require_user_info: None,
             max_downloads: None,
+            message: None,
         }
     }
 
@@ -60,6 +66,7 @@ impl ShareConfig {
             require_login: None,
             require_user_info: None,
             max_downloads: None,
+            message: None,
         }
     }
 
@@ -104,6 +111,11 @@ impl ShareConfig {
         self
     }
 
+    /// Set expiration date to `duration` from now.
+    pub fn expires_in(self, duration: Duration) -> Self {
+        self.expiration_date(Utc::now() + duration)
+    }
+
     /// Set require login.
     pub fn require_login(mut self, require_login: bool) -> Self {
         self.require_login = Some(require_login);
@@ -122,7 +134,22 @@ impl ShareConfig {
         self
     }
 
-    /// Validate the share config.
+    /// Set subject and body of the notification e-mail message. The message is sent to
+    /// recipients only if the share is created with `notify` set.
+    pub fn message<T>(mut self, subject: T, body: T) -> Self
+    where T: Into<String> {
+        self.message = Some((subject.into(), body.into()));
+        self
+    }
+
+    /// Test if the notification e-mail message is set.
+    pub fn has_message(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// Validate the share config locally. The Parent and Items must be resolved to IDs,
+    /// the Request share requires the Parent and the expiration date must be in the future.
+    /// All problems found are listed in the error returned.
     pub fn validate(&self) -> Result<()> {
         let mut errors: String = String::new();
 
@@ -159,6 +186,13 @@ impl ShareConfig {
             }
         }
 
+        // Test expiration date
+        if let Some(ref expiration_date) = self.expiration_date {
+            if *expiration_date < Utc::now() {
+                errors.push_str("Share Expiration Date must be in the future.\n");
+            }
+        }
+
         // Return the error if found any or success
         if errors.is_empty() {
             Ok(())
@@ -170,21 +204,13 @@ impl ShareConfig {
 }
 
 
-// Convert Share Config into JSON Value
-impl serde_json::value::ToJson for ShareConfig {
-    fn to_json(&self) -> Value {
+// Serialize Share Config into the object the API expects
+impl Serialize for ShareConfig {
+    fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+    where S: Serializer {
         use serde_json::value::Map;
         let mut object: Map<String, Value> = Map::new();
 
-        // Check and correct max. downloads
-        let mut max_downloads = self.max_downloads
-            .clone()
-            .unwrap_or(-1);
-
-        if max_downloads <= 0 {
-            max_downloads = -1;
-        }
-
         // Add properties to the object
         // .. don't use stream IDs
         object.insert(
@@ -194,12 +220,6 @@ impl serde_json::value::ToJson for ShareConfig {
         object.insert(
             "ShareType".to_owned(),
             self.kind.to_json());
-        // .. title
-        if let Some(ref title) = self.title {
-            object.insert(
-                "Title".to_owned(),
-                Value::String(title.clone()));
-        }
         // .. require login
         object.insert(
             "RequireLogin".to_owned(),
@@ -219,13 +239,81 @@ impl serde_json::value::ToJson for ShareConfig {
         // .. max. downloads
         object.insert(
             "MaxDownloads".to_owned(),
-            Value::I64(max_downloads as i64));
+            ShareConfig::max_downloads_json(self.max_downloads.clone().unwrap_or(-1)));
+        // .. other properties set
+        self.insert_optional(&mut object);
+
+        Value::Object(object).serialize(serializer)
+    }
+}
+
+
+impl ShareConfig {
+    /// Get the JSON object with only those properties which are set explicitly. The object
+    /// is the body of the request updating the existing share, the share type is never sent.
+    pub fn changes_to_json(&self) -> Value {
+        use serde_json::value::Map;
+        let mut object: Map<String, Value> = Map::new();
+
+        // .. require login
+        if let Some(require_login) = self.require_login {
+            object.insert(
+                "RequireLogin".to_owned(),
+                Value::Bool(require_login));
+        }
+        // .. require user info
+        if let Some(require_user_info) = self.require_user_info {
+            object.insert(
+                "RequireUserInfo".to_owned(),
+                Value::Bool(require_user_info));
+        }
+        // .. max. downloads
+        if let Some(max_downloads) = self.max_downloads {
+            object.insert(
+                "MaxDownloads".to_owned(),
+                ShareConfig::max_downloads_json(max_downloads));
+        }
+        // .. other properties set
+        self.insert_optional(&mut object);
+
+        Value::Object(object)
+    }
+
+    // Unlimited downloads are sent as `-1`
+    fn max_downloads_json(max_downloads: i32) -> Value {
+        if max_downloads <= 0 {
+            Value::I64(-1)
+        }
+        else {
+            Value::I64(max_downloads as i64)
+        }
+    }
+
+    // Insert the optional properties which are set into `object`
+    fn insert_optional(&self, object: &mut serde_json::value::Map<String, Value>) {
+        use serde_json::value::Map;
+
+        // .. title
+        if let Some(ref title) = self.title {
+            object.insert(
+                "Title".to_owned(),
+                Value::String(title.clone()));
+        }
         // .. expiration dtae
         if let Some(ref expiration_date) = self.expiration_date {
             object.insert(
                 "ExpirationDate".to_owned(),
                 Value::String(format!("{}", expiration_date.format("%Y-%m-%d"))));
         }
+        // .. message
+        if let Some((ref subject, ref body)) = self.message {
+            object.insert(
+                "SentMessageTitle".to_owned(),
+                Value::String(subject.clone()));
+            object.insert(
+                "SentMessageBody".to_owned(),
+                Value::String(body.clone()));
+        }
         // .. recipients
         if let Some(ref recipients) = self.recipients {
             let list: Vec<Value> = recipients.iter()
@@ -256,8 +344,98 @@ impl serde_json::value::ToJson for ShareConfig {
                 "Items".to_owned(),
                 Value::Array(list));
         }
+    }
+}
 
-        Value::Object(object)
+
+// Deserialize Share Config from the object the API expects
+impl Deserialize for ShareConfig {
+    fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<ShareConfig, D::Error>
+    where D: Deserializer {
+        use serde::de::Error as DeError;
+
+        Value::deserialize(deserializer)
+            .and_then(|value| ShareConfig::from_json(value)
+                .map_err(|err| D::Error::custom(err.to_string())))
+    }
+}
+
+
+impl ShareConfig {
+    // Construct share config from the decoded JSON value produced by serialization
+    fn from_json(value: Value) -> Result<ShareConfig> {
+        let object = match value.as_object() {
+            Some(v) => v,
+            None => return Err(Error::Other(String::from("Share Config can be constructed from JSON Object only.\n")))
+        };
+
+        let id_of = |v: &Value| v.find("Id")
+            .and_then(|v| v.as_str())
+            .map(|v| Path::Id(v.to_owned()));
+
+        // .. type
+        let kind = match object.get("ShareType") {
+            Some(v) => match Kind::from_json(v.clone()) {
+                Ok(kind) => kind,
+                Err(e) => return Err(e)
+            },
+            None => return Err(Error::Other(String::from("Share Config ShareType property is missing.\n")))
+        };
+
+        // .. expiration date
+        let expiration_date = match object.get("ExpirationDate").and_then(|v| v.as_str()) {
+            Some(v) => match NaiveDate::parse_from_str(v, "%Y-%m-%d") {
+                Ok(date) => Some(DateTime::<Utc>::from_utc(date.and_hms(0, 0, 0), Utc)),
+                Err(err) => return Err(Error::from(err).context("Share Config ExpirationDate property is invalid"))
+            },
+            None => None
+        };
+
+        // .. message
+        let message = match (object.get("SentMessageTitle").and_then(|v| v.as_str()),
+                             object.get("SentMessageBody").and_then(|v| v.as_str())) {
+            (Some(subject), Some(body)) => Some((subject.to_owned(), body.to_owned())),
+            _ => None
+        };
+
+        // .. recipients
+        let recipients = object.get("Recipients")
+            .and_then(|v| v.as_array())
+            .map(|list| list.iter()
+                .filter_map(|v| v.find("User"))
+                .filter_map(|user| {
+                    if let Some(id) = user.find("Id").and_then(|v| v.as_str()) {
+                        Some(UserId::from_id(id))
+                    }
+                    else {
+                        user.find("Email")
+                            .and_then(|v| v.as_str())
+                            .map(|v| UserId::from_email(v))
+                    }
+                })
+                .collect());
+
+        Ok(ShareConfig {
+            kind: kind,
+            title: object.get("Title")
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_owned()),
+            parent: object.get("Parent")
+                .and_then(|v| id_of(v)),
+            items: object.get("Items")
+                .and_then(|v| v.as_array())
+                .map(|list| list.iter().filter_map(|v| id_of(v)).collect()),
+            recipients: recipients,
+            expiration_date: expiration_date,
+            require_login: object.get("RequireLogin")
+                .and_then(|v| v.as_bool()),
+            require_user_info: object.get("RequireUserInfo")
+                .and_then(|v| v.as_bool()),
+            max_downloads: object.get("MaxDownloads")
+                .and_then(|v| v.as_i64())
+                .and_then(|v| if v > 0 { Some(v as i32) } else { None }),
+            message: message,
+        })
     }
 }
 
@@ -281,3 +459,89 @@ impl serde_json::value::ToJson for Path {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use chrono::Duration;
+    use serde_json::value::ToJson;
+    use super::ShareConfig;
+
+    #[test]
+    fn share_config_message_to_json() {
+        let config = ShareConfig::send()
+            .title("Report")
+            .message("Monthly report", "Please find the report attached.");
+        assert!(config.has_message());
+
+        let value = config.to_json();
+        assert_eq!(value.find("Title").and_then(|v| v.as_str()), Some("Report"));
+        assert_eq!(value.find("SentMessageTitle").and_then(|v| v.as_str()), Some("Monthly report"));
+        assert_eq!(value.find("SentMessageBody").and_then(|v| v.as_str()), Some("Please find the report attached."));
+    }
+
+    #[test]
+    fn share_config_without_message_to_json() {
+        let config = ShareConfig::send();
+        assert!(!config.has_message());
+
+        let value = config.to_json();
+        assert!(value.find("SentMessageTitle").is_none());
+        assert!(value.find("SentMessageBody").is_none());
+    }
+
+    #[test]
+    fn share_config_past_expiration_date() {
+        let config = ShareConfig::send()
+            .expiration_date(Utc::now() - Duration::days(1));
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn share_config_future_expiration_date() {
+        let config = ShareConfig::send()
+            .expiration_date(Utc.ymd(2999, 1, 31).and_hms(0, 0, 0));
+        assert!(config.validate().is_ok());
+        assert_eq!(config.to_json().find("ExpirationDate").and_then(|v| v.as_str()), Some("2999-01-31"));
+    }
+
+    #[test]
+    fn share_config_expires_in() {
+        let before = Utc::now();
+        let config = ShareConfig::send()
+            .expires_in(Duration::days(7));
+        let after = Utc::now();
+        assert!(config.validate().is_ok());
+
+        // Compare with the date stored so the test does not depend on the current date
+        let expiration_date = config.expiration_date.unwrap();
+        assert!(expiration_date >= before + Duration::days(7));
+        assert!(expiration_date <= after + Duration::days(7));
+
+        let expected = format!("{}", expiration_date.format("%Y-%m-%d"));
+        assert_eq!(config.to_json().find("ExpirationDate").and_then(|v| v.as_str()), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn share_config_serde_round_trip() {
+        use serde_json;
+        use ::api::items::Path;
+        use ::api::users::UserId;
+
+        let config = ShareConfig::send()
+            .title("Report")
+            .parent(Path::Id("fo-1".to_owned()))
+            .items(vec![ Path::Id("fi-1".to_owned()), Path::Id("fi-2".to_owned()) ])
+            .recipients(vec![ UserId::from_id("u-1"), UserId::from_email("jane@example.com") ])
+            .expiration_date(Utc.ymd(2999, 1, 31).and_hms(0, 0, 0))
+            .require_login(true)
+            .require_user_info(false)
+            .max_downloads(5)
+            .message("Subject", "Body");
+
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: ShareConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, config);
+    }
+}
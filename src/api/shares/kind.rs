@@ -1,4 +1,5 @@
-use serde_json::{self, Value};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde_json::Value;
 use ::error::{Result, Error, IoError, IoErrorKind};
 
 
@@ -42,20 +43,30 @@ impl Kind {
 
 
 // Convert Share Kind into JSON Value
-impl serde_json::value::ToJson for Kind {
-    fn to_json(&self) -> Value {
+impl Serialize for Kind {
+    fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+    where S: Serializer {
         match self {
-            &Kind::Send => {
-                Value::String(String::from("Send"))
-            },
-            &Kind::Request => {
-                Value::String(String::from("Request"))
-            }
+            &Kind::Send => serializer.serialize_str("Send"),
+            &Kind::Request => serializer.serialize_str("Request"),
         }
     }
 }
 
 
+// Construct Share Kind from JSON Value
+impl Deserialize for Kind {
+    fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<Kind, D::Error>
+    where D: Deserializer {
+        use serde::de::Error as DeError;
+
+        Value::deserialize(deserializer)
+            .and_then(|value| Kind::from_json(value)
+                .map_err(|err| D::Error::custom(err.to_string())))
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::Kind;
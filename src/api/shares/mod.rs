@@ -5,19 +5,24 @@ mod kind;
 mod access_right;
 mod config;
 mod share;
+mod recipient;
 
 
 use hyper::method::Method;
-use serde_json::value::ToJson;
+use serde_json::Value;
+use serde_json::value::{ToJson, Map};
 use ::connection::Connection;
 use ::odata::Parameters;
-use ::Result;
+use ::api::MultiOption;
+use ::api::users::UserId;
+use ::{Result, Error};
 
 
 pub use self::kind::Kind;
 pub use self::access_right::AccessRight;
 pub use self::config::ShareConfig;
 pub use self::share::Share;
+pub use self::recipient::ShareRecipient;
 
 
 /// Shares Entity implementation.
@@ -36,8 +41,75 @@ impl Shares {
         }
     }
 
+    /// Get the share identified by `id`.
+    pub fn get(&self, id: &str) -> Result<Share> {
+        self.conn.query_json(Method::Get, format!("Shares({})", id), None, None)
+            .and_then(|v| Share::from_json(v))
+    }
+
+    /// List shares. `parameters` can be used to provide additional options to the API request.
+    pub fn list(&self, parameters: Option<Parameters>) -> Result<MultiOption<Share>> {
+        let url = format!("Shares?{}", parameters.unwrap_or_default().to_string());
+
+        self.conn.query_json(Method::Get, url, None, None)
+            .and_then(|v| Share::from_value(v))
+    }
+
+    /// Delete the share identified by `id`.
+    pub fn delete(&self, id: &str) -> Result<()> {
+        self.conn.query_string(Method::Delete, format!("Shares({})", id), None, None)
+            .map(|_| ())
+    }
+
+    /// Update the share identified by `id` with the properties set explicitly in `changes`,
+    /// e.g. extend its expiration date or change the maximum number of downloads. The share
+    /// type cannot be changed. On success returns the updated share.
+    pub fn update(&self, id: &str, changes: ShareConfig) -> Result<Share> {
+        self.conn.query_json(Method::Patch, format!("Shares({})", id), None, Some(changes.changes_to_json()))
+            .and_then(|v| Share::from_json(v))
+    }
+
+    /// Send the notification e-mail message of the share identified by `id` to `recipients`
+    /// once again, e.g. if they lost the original message. `subject` and `body` are used
+    /// as the subject and the body of the message.
+    pub fn send_email(&self, id: &str, recipients: Vec<UserId>, subject: String, body: String) -> Result<()> {
+        if recipients.is_empty() {
+            return Err(Error::from("Share notification requires at least one recipient"));
+        }
+
+        // Prepare request body
+        let list: Vec<Value> = recipients.iter()
+            .map(|v| {
+                let mut user_object: Map<String, Value> = Map::new();
+                user_object.insert("User".to_owned(), v.to_json());
+                Value::Object(user_object)
+            })
+            .collect();
+
+        let mut object: Map<String, Value> = Map::new();
+        object.insert("Recipients".to_owned(), Value::Array(list));
+        object.insert("SentMessageTitle".to_owned(), Value::String(subject));
+        object.insert("SentMessageBody".to_owned(), Value::String(body));
+
+        self.conn.query_string(Method::Post, format!("Shares({})/Resend", id), None, Some(Value::Object(object).to_string()))
+            .map(|_| ())
+    }
+
+    /// Validate the share configuration given before creating the share with `create()`
+    /// so no share is created if it's invalid. The API has no way of checking the
+    /// configuration without creating the share so only `ShareConfig::validate()`
+    /// is run and no request is sent.
+    pub fn validate_config(&self, config: &ShareConfig) -> Result<()> {
+        config.validate()
+    }
+
     /// Create a new share from the configuration given. 
     pub fn create(&self, config: &ShareConfig, notify: bool) -> Result<Share> {
+        // The message is meaningful for notified recipients only
+        if config.has_message() && !notify {
+            return Err(Error::from("Share message is sent only if recipients are notified"));
+        }
+
         // Prepare request body
         let body = config.to_json();
 
@@ -53,3 +125,140 @@ impl Shares {
             .and_then(|v| Share::from_json(v))
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use ::connection::{Connection, ConnectionSettings};
+    use ::mock::{MockServer, MockResponse};
+    use ::api::MultiOption;
+    use ::odata::Parameters;
+    use super::{Shares, ShareConfig};
+
+    fn shares(server: &MockServer) -> Shares {
+        Shares::new(Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url()))
+    }
+
+    fn share_json(id: &str) -> String {
+        format!("{{\"ShareType\":\"Request\",\"ShareAccessRight\":{{}},\"Id\":\"{}\",\"AliasID\":\"alias-{}\",\"CreationDate\":\"2018-10-01T10:20:30Z\",\"ExpirationDate\":\"9999-12-31T23:59:59Z\",\"TrackUntilDate\":\"9999-12-31T23:59:59Z\",\"MaxDownloads\":-1,\"TotalDownloads\":0,\"SentMessageTitle\":\"\",\"Signature\":\"sig\",\"Title\":\"Upload here\",\"Uri\":\"https://tenant.sharefile.com/r-{}\",\"IsConsumed\":false,\"IsRead\":false,\"RequireLogin\":true,\"RequireUserInfo\":false,\"HasSentMessage\":false}}", id, id, id)
+    }
+
+    #[test]
+    fn get_share() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, share_json("s-1"))]);
+
+        let share = shares(&server).get("s-1").unwrap();
+        assert_eq!(share.id, "s-1");
+        assert!(share.kind.is_request());
+        assert!(share.require_login);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].uri, "/Shares(s-1)");
+    }
+
+    #[test]
+    fn list_shares() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"value\":[{},{}]}}", share_json("s-1"), share_json("s-2")))]);
+
+        match shares(&server).list(Some(Parameters::new().top(2u32))).unwrap() {
+            MultiOption::Many(list) => assert_eq!(list.len(), 2),
+            other => panic!("Unexpected {:?}", other)
+        };
+
+        assert_eq!(server.requests()[0].uri, "/Shares?%24top=2");
+    }
+
+    #[test]
+    fn delete_share() {
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        shares(&server).delete("s-1").unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "DELETE");
+        assert_eq!(requests[0].uri, "/Shares(s-1)");
+    }
+
+    #[test]
+    fn delete_share_not_found() {
+        let server = MockServer::start(vec![
+            MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Share not found\"},\"reason\":\"NotFound\"}")]);
+
+        let err = shares(&server).delete("missing").unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(err.service_code(), Some("NotFound"));
+    }
+
+    #[test]
+    fn create_share_with_message() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, share_json("s-new"))]);
+
+        let config = ShareConfig::request()
+            .parent(::api::items::Path::Id("fo-1".to_owned()))
+            .message("Upload", "Please upload files.");
+
+        let shares = shares(&server);
+        assert!(shares.create(&config, false).is_err());
+        assert_eq!(server.requests().len(), 0);
+
+        shares.create(&config, true).unwrap();
+        let requests = server.requests();
+        assert_eq!(requests[0].uri, "/Shares?notify=true");
+        assert!(requests[0].body_string().contains("\"SentMessageBody\":\"Please upload files.\""));
+    }
+
+    #[test]
+    fn update_share() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, share_json("s-1"))]);
+
+        let changes = ShareConfig::send()
+            .title("Updated")
+            .max_downloads(5);
+
+        let share = shares(&server).update("s-1", changes).unwrap();
+        assert_eq!(share.id, "s-1");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "PATCH");
+        assert_eq!(requests[0].uri, "/Shares(s-1)");
+        assert_eq!(requests[0].body_string(), "{\"MaxDownloads\":5,\"Title\":\"Updated\"}");
+    }
+
+    #[test]
+    fn send_email() {
+        use ::api::users::UserId;
+
+        let server = MockServer::start(vec![
+            MockResponse::new(204, "")]);
+
+        let shares = shares(&server);
+        assert!(shares.send_email("s-1", Vec::new(), "Files".to_owned(), "See files.".to_owned()).is_err());
+        assert_eq!(server.requests().len(), 0);
+
+        shares.send_email("s-1", vec![UserId::from_id("u-1")], "Files".to_owned(), "See files.".to_owned()).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Shares(s-1)/Resend");
+        assert_eq!(requests[0].body_string(), "{\"Recipients\":[{\"User\":{\"Id\":\"u-1\"}}],\"SentMessageBody\":\"See files.\",\"SentMessageTitle\":\"Files\"}");
+    }
+
+    #[test]
+    fn validate_config() {
+        use ::api::items::Path;
+
+        let server = MockServer::start(Vec::new());
+        let shares = shares(&server);
+
+        let err = shares.validate_config(&ShareConfig::request()).unwrap_err();
+        assert_eq!(err.to_string(), "Request Share requires Parent.\n");
+        assert!(shares.validate_config(&ShareConfig::request().parent(Path::Id("fo-1".to_owned()))).is_ok());
+        assert_eq!(server.requests().len(), 0);
+    }
+}
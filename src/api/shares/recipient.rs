@@ -0,0 +1,71 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde_json::Value;
+use ::error::{Result, Error, IoError, IoErrorKind};
+
+
+/// Share Recipient
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ShareRecipient {
+    /// ID of the recipient user.
+    pub id: String,
+    /// E-mail address of the recipient user.
+    pub email: String,
+    /// Full name of the recipient user.
+    pub name: String,
+}
+
+
+impl ShareRecipient {
+    /// Construct recipient from the decoded JSON value. The user details can be
+    /// nested in `User` property as the API returns them.
+    pub fn from_json(value: Value) -> Result<ShareRecipient> {
+        let user = match value.find("User") {
+            Some(user) => user.clone(),
+            None => value,
+        };
+
+        if let Some(object) = user.as_object() {
+            let read = |name: &str| object.get(name)
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_owned());
+
+            Ok(ShareRecipient {
+                id: read("Id").unwrap_or_default(),
+                email: read("Email").unwrap_or_default(),
+                name: read("FullName").or_else(|| read("Name")).unwrap_or_default(),
+            })
+        }
+        else {
+            Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.Recipients can be constructed from JSON Object only."))
+        }
+    }
+}
+
+
+// Serialize with the same property names the API uses for the user
+impl Serialize for ShareRecipient {
+    fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+    where S: Serializer {
+        use serde_json::value::Map;
+        let mut object: Map<String, Value> = Map::new();
+
+        object.insert("Id".to_owned(), Value::String(self.id.clone()));
+        object.insert("Email".to_owned(), Value::String(self.email.clone()));
+        object.insert("FullName".to_owned(), Value::String(self.name.clone()));
+
+        Value::Object(object).serialize(serializer)
+    }
+}
+
+
+// Deserialize through the JSON Value representation
+impl Deserialize for ShareRecipient {
+    fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<ShareRecipient, D::Error>
+    where D: Deserializer {
+        use serde::de::Error as DeError;
+
+        Value::deserialize(deserializer)
+            .and_then(|value| ShareRecipient::from_json(value)
+                .map_err(|err| D::Error::custom(err.to_string())))
+    }
+}
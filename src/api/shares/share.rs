@@ -1,7 +1,10 @@
 use chrono::prelude::*;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde_json::Value;
+use serde_json::value::ToJson;
+use ::api::MultiOption;
 use ::error::{Result, Error, IoError, IoErrorKind};
-use super::{Kind, AccessRight};
+use super::{Kind, AccessRight, ShareRecipient};
 
 
 /// Share Details
@@ -14,25 +17,25 @@ pub struct Share {
     /// ID of the share.
     pub id: String,
     /// Alias ID of the share.
-    pub alias_id: String,
+    pub alias_id: Option<String>,
     /// Date the share was created.
-    pub creation_date: DateTime<Utc>,
+    pub creation_date: Option<DateTime<Utc>>,
     /// Date the share expires.
-    pub expiration_date: DateTime<Utc>,
+    pub expiration_date: Option<DateTime<Utc>>,
     /// User activity on this share will be tracked up to this date.
-    pub track_until_date: DateTime<Utc>,
-    /// Maximum number of downloads each user can perform.
+    pub track_until_date: Option<DateTime<Utc>>,
+    /// Maximum number of downloads each user can perform, `-1` if unlimited.
     pub max_downloads: i32,
     /// Total number of times a share has been downloaded by a user.
     pub total_downloads: i32,
     /// Subject of Share email message.
-    pub sent_message_title: String,
+    pub sent_message_title: Option<String>,
     /// HMAC Signature for the share data.
-    pub signature: String,
+    pub signature: Option<String>,
     /// Share title.
-    pub title: String,
+    pub title: Option<String>,
     /// Uri to access the share through the Web portal.
-    pub uri: String,
+    pub uri: Option<String>,
     /// Indicates whether or not this share has been archived.
     pub is_archived: bool,
     /// Indicates whether or not this share has been downloaded.
@@ -49,11 +52,44 @@ pub struct Share {
     pub has_sent_message: bool,
     /// When enabled the items are identified by stream IDs instead of item IDs. Applies to Send Shares only.
     pub uses_stream_ids: bool,
+    /// Users the share is sent to. Empty if the API does not return recipients.
+    pub recipients: Vec<ShareRecipient>,
 }
 
 
 impl Share {
-    /// Construct share from the decoded JSON value.
+    /// Construct share(s) from the decoded JSON value of the share or the share collection.
+    pub fn from_value(value: Value) -> Result<MultiOption<Share>> {
+        // Check if we have one share or many
+        match value.find("odata.count") {
+            Some(_) => {
+                let mut shares = Vec::new();
+                if let Some(list) = value.find("value").and_then(|v| v.as_array()) {
+                    for val in list {
+                        match Share::from_json(val.clone()) {
+                            Ok(share) => shares.push(share),
+                            Err(e) => return Err(e)
+                        };
+                    }
+                }
+
+                if shares.is_empty() {
+                    Ok(MultiOption::None)
+                }
+                else {
+                    Ok(MultiOption::Many(shares))
+                }
+            },
+            None => {
+                Share::from_json(value)
+                    .map(|share| MultiOption::One(share))
+            }
+        }
+    }
+
+    /// Construct share from the decoded JSON value. Only `Id` and `ShareType` properties
+    /// are required so shares requested with `$select` can be constructed, other properties
+    /// missing are set to `None` or to the default value.
     pub fn from_json(value: Value) -> Result<Share> {
         if let Some(object) = value.as_object() {
             // Read share type
@@ -65,154 +101,141 @@ impl Share {
                 None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.ShareType property is missing.")),
             };
 
-            // Read access right
+            // Read access right (nothing is granted if the property is missing)
             let access_right = match object.get("ShareAccessRight") {
                 Some(v) => match AccessRight::from_json(v.clone()) {
                     Ok(access_right) => access_right,
                     Err(e) => return Err(e),
                 },
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.ShareAccessRight property is missing.")),
+                None => AccessRight::none(),
             };
 
             // Read ID
             let id = match object.get("Id") {
-                Some(v) => v.as_str().unwrap(),
+                Some(v) => match v.as_str() {
+                    Some(v) => v.to_owned(),
+                    None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.Id property must be a string.")),
+                },
                 None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.Id property is missing.")),
             };
 
-            // Read Alias ID
-            let alias_id = match object.get("AliasID") {
-                Some(v) => v.as_str().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.AliasID property is missing.")),
+            // Read dates
+            let creation_date = match read_date(&value, "CreationDate", "Share.CreationDate property is invalid") {
+                Ok(dt) => dt,
+                Err(e) => return Err(e),
             };
 
-            // Read creation date
-            let creation_date = match object.get("CreationDate") {
-                Some(v) => match v.as_str().unwrap().parse::<DateTime<Utc>>() {
-                    Ok(dt) => dt,
-                    Err(err) => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.CreationDate property is invalid because {}", err)))
-                },
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.CreationDate property is missing.")),
+            let expiration_date = match read_date(&value, "ExpirationDate", "Share.ExpirationDate property is invalid") {
+                Ok(dt) => dt,
+                Err(e) => return Err(e),
             };
 
-            // Read expiration date
-            let expiration_date = match object.get("ExpirationDate") {
-                Some(v) => match v.as_str().unwrap().parse::<DateTime<Utc>>() {
-                    Ok(dt) => dt,
-                    Err(err) => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.ExpirationDate property is invalid because {}", err)))
-                },
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.ExpirationDate property is missing.")),
+            let track_until_date = match read_date(&value, "TrackUntilDate", "Share.TrackUntilDate property is invalid") {
+                Ok(dt) => dt,
+                Err(e) => return Err(e),
             };
 
-            // Read track until date
-            let track_until_date = match object.get("TrackUntilDate") {
-                Some(v) => match v.as_str().unwrap().parse::<DateTime<Utc>>() {
-                    Ok(dt) => dt,
-                    Err(err) => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.TrackUntilDate property is invalid because {}", err)))
-                },
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.TrackUntilDate property is missing.")),
-            };
+            // Read downloads counters
+            let max_downloads = value.find("MaxDownloads")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(-1) as i32;
 
-            // Read max. downloads
-            let max_downloads = match value.find("MaxDownloads") {
-                Some(v) => v.as_i64().unwrap() as i32,
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.MaxDownloads property is missing."))
-            };
+            let total_downloads = value.find("TotalDownloads")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0) as i32;
 
-            // Read total downloads
-            let total_downloads = match value.find("TotalDownloads") {
-                Some(v) => v.as_i64().unwrap() as i32,
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.TotalDownloads property is missing."))
-            };
+            // Read recipients (this property is optional)
+            let mut recipients = Vec::new();
+            if let Some(list) = object.get("Recipients").and_then(|v| v.as_array()) {
+                for val in list {
+                    match ShareRecipient::from_json(val.clone()) {
+                        Ok(recipient) => recipients.push(recipient),
+                        Err(e) => return Err(e),
+                    };
+                }
+            }
 
-            // Read sent message title
-            let sent_message_title = match object.get("SentMessageTitle") {
-                Some(v) => v.as_str().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.SentMessageTitle property is missing.")),
+            // Read optional strings and flags
+            let alias_id = match read_string(&value, "AliasID") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read signature
-            let signature = match object.get("Signature") {
-                Some(v) => v.as_str().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.Signature property is missing.")),
+            let sent_message_title = match read_string(&value, "SentMessageTitle") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read title
-            let title = match object.get("Title") {
-                Some(v) => v.as_str().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.Title property is missing.")),
+            let signature = match read_string(&value, "Signature") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read URI
-            let uri = match object.get("Uri") {
-                Some(v) => v.as_str().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.Uri property is missing.")),
+            let title = match read_string(&value, "Title") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read archived flag (this property is probably optional)
-            let is_archived = match object.get("IsArchived") {
-                Some(v) => v.as_bool().unwrap(),
-                None => false,
+            let uri = match read_string(&value, "Uri") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read consumed flag
-            let is_consumed = match object.get("IsConsumed") {
-                Some(v) => v.as_bool().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.IsConsumed property is missing.")),
+            let is_archived = match read_bool(&value, "IsArchived") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read read flag
-            let is_read = match object.get("IsRead") {
-                Some(v) => v.as_bool().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.IsRead property is missing.")),
+            let is_consumed = match read_bool(&value, "IsConsumed") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read view only flag (this property is probably optional)
-            let is_view_only = match object.get("IsViewOnly") {
-                Some(v) => v.as_bool().unwrap(),
-                None => false,
+            let is_read = match read_bool(&value, "IsRead") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read require login flag
-            let require_login = match object.get("RequireLogin") {
-                Some(v) => v.as_bool().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.RequireLogin property is missing.")),
+            let is_view_only = match read_bool(&value, "IsViewOnly") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read require user info flag
-            let require_user_info = match object.get("RequireUserInfo") {
-                Some(v) => v.as_bool().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.RequireUserInfo property is missing.")),
+            let require_login = match read_bool(&value, "RequireLogin") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read message sent flag
-            let has_sent_message = match object.get("HasSentMessage") {
-                Some(v) => v.as_bool().unwrap(),
-                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Share.HasSentMessage property is missing.")),
+            let require_user_info = match read_bool(&value, "RequireUserInfo") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
-            // Read uses stream IDs flag (this property is probably optional)
-            let uses_stream_ids = match object.get("UsesStreamIDs") {
-                Some(v) => v.as_bool().unwrap(),
-                None => false,
+            let has_sent_message = match read_bool(&value, "HasSentMessage") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
             };
 
+            let uses_stream_ids = match read_bool(&value, "UsesStreamIDs") {
+                Ok(v) => v,
+                Err(e) => return Err(e),
+            };
 
             // Construct the Share struct
             Ok(Share {
                 kind: kind,
                 access_right: access_right,
-                id: id.to_owned(),
-                alias_id: alias_id.to_owned(),
+                id: id,
+                alias_id: alias_id,
                 creation_date: creation_date,
                 expiration_date: expiration_date,
                 track_until_date: track_until_date,
                 max_downloads: max_downloads,
                 total_downloads: total_downloads,
-                sent_message_title: sent_message_title.to_owned(),
-                signature: signature.to_owned(),
-                title: title.to_owned(),
-                uri: uri.to_owned(),
+                sent_message_title: sent_message_title,
+                signature: signature,
+                title: title,
+                uri: uri,
                 is_archived: is_archived,
                 is_consumed: is_consumed,
                 is_read: is_read,
@@ -221,6 +244,7 @@ impl Share {
                 require_user_info: require_user_info,
                 has_sent_message: has_sent_message,
                 uses_stream_ids: uses_stream_ids,
+                recipients: recipients,
             })
         }
         else {
@@ -228,3 +252,257 @@ impl Share {
         }
     }
 }
+
+
+// Serialize with the same property names the API uses so the share can be constructed
+// back with `from_json()`
+impl Serialize for Share {
+    fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+    where S: Serializer {
+        use serde_json::value::Map;
+        let mut object: Map<String, Value> = Map::new();
+
+        object.insert("ShareType".to_owned(), self.kind.to_json());
+        object.insert("ShareAccessRight".to_owned(), self.access_right.to_json());
+        object.insert("Id".to_owned(), Value::String(self.id.clone()));
+        object.insert("MaxDownloads".to_owned(), Value::I64(self.max_downloads as i64));
+        object.insert("TotalDownloads".to_owned(), Value::I64(self.total_downloads as i64));
+
+        // .. optional strings
+        let strings = vec![
+            ("AliasID", &self.alias_id),
+            ("SentMessageTitle", &self.sent_message_title),
+            ("Signature", &self.signature),
+            ("Title", &self.title),
+            ("Uri", &self.uri) ];
+
+        for (name, value) in strings {
+            if let &Some(ref value) = value {
+                object.insert(name.to_owned(), Value::String(value.clone()));
+            }
+        }
+
+        // .. optional dates
+        let dates = vec![
+            ("CreationDate", &self.creation_date),
+            ("ExpirationDate", &self.expiration_date),
+            ("TrackUntilDate", &self.track_until_date) ];
+
+        for (name, value) in dates {
+            if let &Some(ref value) = value {
+                object.insert(name.to_owned(), Value::String(value.to_rfc3339()));
+            }
+        }
+
+        // .. flags
+        let flags = vec![
+            ("IsArchived", self.is_archived),
+            ("IsConsumed", self.is_consumed),
+            ("IsRead", self.is_read),
+            ("IsViewOnly", self.is_view_only),
+            ("RequireLogin", self.require_login),
+            ("RequireUserInfo", self.require_user_info),
+            ("HasSentMessage", self.has_sent_message),
+            ("UsesStreamIDs", self.uses_stream_ids) ];
+
+        for (name, value) in flags {
+            object.insert(name.to_owned(), Value::Bool(value));
+        }
+
+        // .. recipients
+        let recipients: Vec<Value> = self.recipients.iter()
+            .map(|v| v.to_json())
+            .collect();
+
+        object.insert("Recipients".to_owned(), Value::Array(recipients));
+
+        Value::Object(object).serialize(serializer)
+    }
+}
+
+
+// Deserialize through the JSON Value representation
+impl Deserialize for Share {
+    fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<Share, D::Error>
+    where D: Deserializer {
+        use serde::de::Error as DeError;
+
+        Value::deserialize(deserializer)
+            .and_then(|value| Share::from_json(value)
+                .map_err(|err| D::Error::custom(err.to_string())))
+    }
+}
+
+
+// Read the optional string property, missing and `null` properties are `None`
+fn read_string(value: &Value, name: &str) -> Result<Option<String>> {
+    match value.find(name) {
+        Some(&Value::Null) | None => Ok(None),
+        Some(v) => match v.as_str() {
+            Some(v) => Ok(Some(v.to_owned())),
+            None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.{} property must be a string.", name))),
+        },
+    }
+}
+
+
+// Read the optional flag, missing and `null` flags are `false`
+fn read_bool(value: &Value, name: &str) -> Result<bool> {
+    match value.find(name) {
+        Some(&Value::Null) | None => Ok(false),
+        Some(v) => match v.as_bool() {
+            Some(v) => Ok(v),
+            None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.{} property must be a boolean.", name))),
+        },
+    }
+}
+
+
+// Read the optional date and time property, `context` describes the parse error
+fn read_date(value: &Value, name: &str, context: &'static str) -> Result<Option<DateTime<Utc>>> {
+    match value.find(name) {
+        Some(&Value::Null) | None => Ok(None),
+        Some(v) => match v.as_str().map(|v| v.parse::<DateTime<Utc>>()) {
+            Some(Ok(dt)) => Ok(Some(dt)),
+            Some(Err(err)) => Err(Error::from(err).context(context)),
+            None => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Share.{} property must be a string.", name))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use ::api::MultiOption;
+    use ::error::Error;
+    use super::{Share, Kind, AccessRight};
+
+    fn share_json(id: &str) -> String {
+        format!("{{\"ShareType\":\"Send\",\"ShareAccessRight\":{{}},\"Id\":\"{}\",\"AliasID\":\"alias-{}\",\"CreationDate\":\"2018-10-01T10:20:30Z\",\"ExpirationDate\":\"9999-12-31T23:59:59Z\",\"TrackUntilDate\":\"9999-12-31T23:59:59Z\",\"MaxDownloads\":-1,\"TotalDownloads\":0,\"SentMessageTitle\":\"Files\",\"Signature\":\"sig\",\"Title\":\"Report\",\"Uri\":\"https://tenant.sharefile.com/d-{}\",\"IsConsumed\":false,\"IsRead\":false,\"RequireLogin\":false,\"RequireUserInfo\":false,\"HasSentMessage\":true}}", id, id, id)
+    }
+
+    #[test]
+    fn share_from_json() {
+        let share = Share::from_json(serde_json::from_str(&share_json("s-1")).unwrap()).unwrap();
+        assert_eq!(share.kind, Kind::Send);
+        assert_eq!(share.id, "s-1");
+        assert_eq!(share.alias_id, Some("alias-s-1".to_owned()));
+        assert_eq!(share.title, Some("Report".to_owned()));
+        assert!(share.creation_date.is_some());
+        assert_eq!(share.max_downloads, -1);
+        assert!(share.has_sent_message);
+        assert!(!share.is_archived);
+    }
+
+    #[test]
+    fn share_from_selected_json() {
+        let value = "{\"ShareType\":\"Request\",\"Id\":\"s-1\",\"Title\":\"Upload here\"}";
+        let share = Share::from_json(serde_json::from_str(value).unwrap()).unwrap();
+        assert_eq!(share.kind, Kind::Request);
+        assert_eq!(share.id, "s-1");
+        assert_eq!(share.title, Some("Upload here".to_owned()));
+        assert_eq!(share.uri, None);
+        assert_eq!(share.signature, None);
+        assert_eq!(share.creation_date, None);
+        assert_eq!(share.max_downloads, -1);
+        assert_eq!(share.access_right, AccessRight::none());
+        assert!(!share.require_login);
+    }
+
+    #[test]
+    fn share_from_json_without_id() {
+        let value = "{\"ShareType\":\"Request\",\"Title\":\"Upload here\"}";
+        assert!(Share::from_json(serde_json::from_str(value).unwrap()).is_err());
+    }
+
+    #[test]
+    fn share_from_json_numeric_id() {
+        let value = "{\"ShareType\":\"Send\",\"Id\":42}";
+        match Share::from_json(serde_json::from_str(value).unwrap()) {
+            Err(Error::Io(ref err)) => assert_eq!(err.to_string(), "Share.Id property must be a string."),
+            other => panic!("Expected invalid input error but got {:?}", other),
+        };
+
+        let value = "{\"ShareType\":\"Send\",\"Id\":\"s-1\",\"CreationDate\":20181001}";
+        assert!(Share::from_json(serde_json::from_str(value).unwrap()).is_err());
+    }
+
+    #[test]
+    fn share_from_json_wrong_types() {
+        let value = "{\"ShareType\":\"Send\",\"Id\":\"s-1\",\"Title\":42}";
+        match Share::from_json(serde_json::from_str(value).unwrap()) {
+            Err(Error::Io(ref err)) => assert_eq!(err.to_string(), "Share.Title property must be a string."),
+            other => panic!("Expected invalid input error but got {:?}", other),
+        };
+
+        let value = "{\"ShareType\":\"Send\",\"Id\":\"s-1\",\"IsArchived\":\"true\"}";
+        match Share::from_json(serde_json::from_str(value).unwrap()) {
+            Err(Error::Io(ref err)) => assert_eq!(err.to_string(), "Share.IsArchived property must be a boolean."),
+            other => panic!("Expected invalid input error but got {:?}", other),
+        };
+
+        // Nulls are the same as missing properties
+        let value = "{\"ShareType\":\"Send\",\"Id\":\"s-1\",\"Title\":null,\"IsArchived\":null}";
+        let share = Share::from_json(serde_json::from_str(value).unwrap()).unwrap();
+        assert_eq!(share.title, None);
+        assert!(!share.is_archived);
+    }
+
+    #[test]
+    fn share_from_json_invalid_date() {
+        let value = "{\"ShareType\":\"Send\",\"Id\":\"s-1\",\"ExpirationDate\":\"never\"}";
+        assert!(Share::from_json(serde_json::from_str(value).unwrap()).is_err());
+    }
+
+    #[test]
+    fn share_without_recipients() {
+        let share = Share::from_json(serde_json::from_str(&share_json("s-1")).unwrap()).unwrap();
+        assert!(share.recipients.is_empty());
+    }
+
+    #[test]
+    fn share_with_recipients() {
+        let value = share_json("s-1").trim_right_matches('}').to_owned()
+            + ",\"Recipients\":[{\"Id\":\"alias-1\",\"User\":{\"Id\":\"u-1\",\"Email\":\"jane@example.com\",\"FullName\":\"Jane Doe\"}},{\"User\":{\"Id\":\"u-2\",\"Email\":\"john@example.com\"}}]}";
+        let share = Share::from_json(serde_json::from_str(&value).unwrap()).unwrap();
+
+        assert_eq!(share.recipients.len(), 2);
+        assert_eq!(share.recipients[0].id, "u-1");
+        assert_eq!(share.recipients[0].email, "jane@example.com");
+        assert_eq!(share.recipients[0].name, "Jane Doe");
+        assert_eq!(share.recipients[1].id, "u-2");
+        assert_eq!(share.recipients[1].name, "");
+    }
+
+    #[test]
+    fn share_serde_round_trip() {
+        let value = share_json("s-1").trim_right_matches('}').to_owned()
+            + ",\"ShareAccessRight\":{\"CanView\":true,\"CanDownload\":true,\"CanUpload\":false,\"CanDelete\":false},\"Recipients\":[{\"User\":{\"Id\":\"u-1\",\"Email\":\"jane@example.com\",\"FullName\":\"Jane Doe\"}}]}";
+        let share = Share::from_json(serde_json::from_str(&value).unwrap()).unwrap();
+
+        let json = serde_json::to_string(&share).unwrap();
+        let restored: Share = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, share);
+    }
+
+    #[test]
+    fn share_from_value_one() {
+        let shares = Share::from_value(serde_json::from_str(&share_json("s-1")).unwrap()).unwrap();
+        assert!(shares.is_one());
+    }
+
+    #[test]
+    fn share_from_value_collection() {
+        let value = format!("{{\"odata.count\":2,\"value\":[{},{}]}}", share_json("s-1"), share_json("s-2"));
+        match Share::from_value(serde_json::from_str(&value).unwrap()).unwrap() {
+            MultiOption::Many(shares) => assert_eq!(shares.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), vec!["s-1", "s-2"]),
+            other => panic!("Unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn share_from_value_empty_collection() {
+        let value = serde_json::from_str("{\"odata.count\":0,\"value\":[]}").unwrap();
+        assert!(Share::from_value(value).unwrap().is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use serde_json::{self, Value};
+use ::{Error, Result};
+
+
+/// User configuration.
+///
+/// Additional information can be found in [the official documentation](http://api.sharefile.com/rest/docs/resource.aspx?name=ShareFile.Api.Models.User).
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UserConfig {
+    /// If set to `true` the user is created as the employee of the account.
+    employee: bool,
+    /// Primary e-mail address of the user.
+    email: String,
+    /// First name of the user.
+    first_name: Option<String>,
+    /// Last name of the user.
+    last_name: Option<String>,
+    /// Company of the user.
+    company: Option<String>,
+    /// Password of the user.
+    password: Option<String>,
+}
+
+
+impl UserConfig {
+    /// Create the new configuration of the client user with the e-mail address given.
+    pub fn client<T>(email: T) -> UserConfig
+    where T: Into<String> {
+        UserConfig {
+            employee: false,
+            email: email.into(),
+            first_name: None,
+            last_name: None,
+            company: None,
+            password: None,
+        }
+    }
+
+    /// Create the new configuration of the employee user with the e-mail address given.
+    pub fn employee<T>(email: T) -> UserConfig
+    where T: Into<String> {
+        UserConfig {
+            employee: true,
+            .. UserConfig::client(email)
+        }
+    }
+
+    /// Test if the user is the employee.
+    pub fn is_employee(&self) -> bool {
+        self.employee
+    }
+
+    /// Set first name.
+    pub fn first_name<T>(mut self, first_name: T) -> Self
+    where T: Into<String> {
+        self.first_name = Some(first_name.into());
+        self
+    }
+
+    /// Set last name.
+    pub fn last_name<T>(mut self, last_name: T) -> Self
+    where T: Into<String> {
+        self.last_name = Some(last_name.into());
+        self
+    }
+
+    /// Set company.
+    pub fn company<T>(mut self, company: T) -> Self
+    where T: Into<String> {
+        self.company = Some(company.into());
+        self
+    }
+
+    /// Set password.
+    pub fn password<T>(mut self, password: T) -> Self
+    where T: Into<String> {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Validate the user config.
+    pub fn validate(&self) -> Result<()> {
+        if self.email.contains('@') {
+            Ok(())
+        }
+        else {
+            Err(Error::Other(String::from("User Email must be the valid e-mail address.\n")))
+        }
+    }
+}
+
+
+// Convert User Config into JSON Value
+impl serde_json::value::ToJson for UserConfig {
+    fn to_json(&self) -> Value {
+        use serde_json::value::Map;
+        let mut object: Map<String, Value> = Map::new();
+
+        object.insert(
+            "Email".to_owned(),
+            Value::String(self.email.clone()));
+
+        if let Some(ref first_name) = self.first_name {
+            object.insert(
+                "FirstName".to_owned(),
+                Value::String(first_name.clone()));
+        }
+
+        if let Some(ref last_name) = self.last_name {
+            object.insert(
+                "LastName".to_owned(),
+                Value::String(last_name.clone()));
+        }
+
+        if let Some(ref company) = self.company {
+            object.insert(
+                "Company".to_owned(),
+                Value::String(company.clone()));
+        }
+
+        if let Some(ref password) = self.password {
+            object.insert(
+                "Password".to_owned(),
+                Value::String(password.clone()));
+        }
+
+        Value::Object(object)
+    }
+}
+
+
+// Format without the password so it does not leak into logs
+impl fmt::Debug for UserConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UserConfig")
+            .field("employee", &self.employee)
+            .field("email", &self.email)
+            .field("first_name", &self.first_name)
+            .field("last_name", &self.last_name)
+            .field("company", &self.company)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::UserConfig;
+
+    #[test]
+    fn debug_redacts_password() {
+        let config = UserConfig::client("jane@example.com")
+            .password("secret");
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("jane@example.com"));
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -2,6 +2,213 @@
 
 
 mod user_id;
+mod user;
+mod config;
+
+
+use hyper::method::Method;
+use serde_json::value::ToJson;
+use ::connection::Connection;
+use ::odata::Parameters;
+use ::api::MultiOption;
+use ::{Result, Error};
 
 
 pub use self::user_id::UserId;
+pub use self::user::User;
+pub use self::config::UserConfig;
+
+
+/// Users Entity implementation.
+///
+/// Users struct implemets methods of [Users API Entity](http://api.sharefile.com/rest/docs/resource.aspx?name=Users)
+pub struct Users {
+    conn: Connection,
+}
+
+
+impl Users {
+    /// Create a new instance of User Entities API
+    pub fn new(conn: Connection) -> Self {
+        Users {
+            conn: conn,
+        }
+    }
+
+    /// Get the user identified by `id`.
+    pub fn get(&self, id: UserId) -> Result<User> {
+        let url = match id {
+            UserId::Id(id) => format!("Users({})", id),
+            UserId::Email(mailbox) => {
+                let parameters = Parameters::new()
+                    .custom(vec![ ("emailaddress", mailbox.address) ]);
+
+                format!("Users?{}", parameters.to_string())
+            }
+        };
+
+        self.conn.query_json(Method::Get, url, None, None)
+            .and_then(|v| User::from_json(v))
+    }
+
+    /// Get the user the connection is authenticated as.
+    pub fn me(&self) -> Result<User> {
+        self.conn.query_json(Method::Get, String::from("Users(me)"), None, None)
+            .and_then(|v| User::from_json(v))
+    }
+
+    /// List users. `parameters` can be used to provide additional options to the API request.
+    pub fn list(&self, parameters: Option<Parameters>) -> Result<MultiOption<User>> {
+        let url = format!("Users?{}", parameters.unwrap_or_default().to_string());
+
+        self.conn.query_json(Method::Get, url, None, None)
+            .and_then(|v| User::from_value(v))
+    }
+
+    /// Create a new user from the configuration given.
+    pub fn create(&self, config: &UserConfig) -> Result<User> {
+        if let Err(e) = config.validate() {
+            return Err(e);
+        }
+
+        // Employees and clients are created with different endpoints
+        let url = if config.is_employee() {
+            String::from("Users/AccountUser")
+        }
+        else {
+            String::from("Users")
+        };
+
+        self.conn.query_json(Method::Post, url, None, Some(config.to_json()))
+            .and_then(|v| User::from_json(v))
+    }
+
+    /// Delete the user identified by `id`.
+    pub fn delete(&self, id: UserId) -> Result<()> {
+        // Resolve the e-mail address to the ID first
+        let id = match id {
+            UserId::Id(id) => id,
+            UserId::Email(_) => match self.get(id) {
+                Ok(user) => user.id,
+                Err(e) => return Err(e)
+            }
+        };
+
+        if id.is_empty() {
+            return Err(Error::from("The User ID is empty"));
+        }
+
+        self.conn.query_string(Method::Delete, format!("Users({})", id), None, None)
+            .map(|_| ())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ::connection::{Connection, ConnectionSettings};
+    use ::mock::{MockServer, MockResponse};
+    use ::api::MultiOption;
+    use ::odata::Parameters;
+    use super::{Users, UserId, UserConfig};
+
+    fn users(server: &MockServer) -> Users {
+        Users::new(Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url()))
+    }
+
+    fn user_json(id: &str, email: &str) -> String {
+        format!("{{\"Id\":\"{}\",\"Email\":\"{}\",\"FirstName\":\"Jane\",\"LastName\":\"Doe\"}}", id, email)
+    }
+
+    #[test]
+    fn get_user_by_id() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, user_json("u-1", "jane@example.com"))]);
+
+        let user = users(&server).get(UserId::from_id("u-1")).unwrap();
+        assert_eq!(user.id, "u-1");
+        assert_eq!(user.email, "jane@example.com");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].uri, "/Users(u-1)");
+    }
+
+    #[test]
+    fn get_user_by_email() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, user_json("u-1", "jane@example.com"))]);
+
+        users(&server).get(UserId::from_email("jane@example.com")).unwrap();
+        assert_eq!(server.requests()[0].uri, "/Users?emailaddress=jane%40example.com");
+    }
+
+    #[test]
+    fn get_me() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "{\"odata.metadata\":\"https://tenant.sf-api.com/sf/v3/$metadata#Users/ShareFile.Api.Models.AccountUser@Element\",\"odata.type\":\"ShareFile.Api.Models.AccountUser\",\"Id\":\"u-me\",\"Email\":\"me@example.com\",\"FirstName\":\"John\",\"LastName\":\"Smith\",\"FullName\":\"John Smith\",\"Company\":\"ACME\",\"IsEmployee\":true,\"IsConfirmed\":true,\"DateCreated\":\"2017-03-14T09:26:53.000Z\",\"url\":\"https://tenant.sf-api.com/sf/v3/Users(u-me)\"}")]);
+
+        let user = users(&server).me().unwrap();
+        assert_eq!(user.id, "u-me");
+        assert_eq!(user.full_name, "John Smith");
+        assert_eq!(user.email, "me@example.com");
+        assert!(user.is_employee);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].uri, "/Users(me)");
+        assert_eq!(requests[0].header("Authorization"), Some("Bearer token"));
+    }
+
+    #[test]
+    fn list_users() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, format!("{{\"odata.count\":2,\"value\":[{},{}]}}", user_json("u-1", "a@example.com"), user_json("u-2", "b@example.com")))]);
+
+        let found = users(&server).list(Some(Parameters::new().top(2u32))).unwrap();
+        match found {
+            MultiOption::Many(list) => assert_eq!(list.len(), 2),
+            other => panic!("Unexpected {:?}", other)
+        };
+
+        assert_eq!(server.requests()[0].uri, "/Users?%24top=2");
+    }
+
+    #[test]
+    fn create_user() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, user_json("u-new", "new@example.com")),
+            MockResponse::new(200, user_json("u-staff", "staff@example.com"))]);
+
+        let users = users(&server);
+        let user = users.create(&UserConfig::client("new@example.com").first_name("Jane")).unwrap();
+        assert_eq!(user.id, "u-new");
+        users.create(&UserConfig::employee("staff@example.com")).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].uri, "/Users");
+        assert!(requests[0].body_string().contains("\"Email\":\"new@example.com\""));
+        assert!(requests[0].body_string().contains("\"FirstName\":\"Jane\""));
+        assert_eq!(requests[1].uri, "/Users/AccountUser");
+    }
+
+    #[test]
+    fn create_user_invalid() {
+        let server = MockServer::start(vec![]);
+        assert!(users(&server).create(&UserConfig::client("nobody")).is_err());
+        assert_eq!(server.requests().len(), 0);
+    }
+
+    #[test]
+    fn delete_user() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, user_json("u-1", "jane@example.com")),
+            MockResponse::new(204, "")]);
+
+        users(&server).delete(UserId::from_email("jane@example.com")).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[1].method, "DELETE");
+        assert_eq!(requests[1].uri, "/Users(u-1)");
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::prelude::*;
+use serde_json::Value;
+use ::api::MultiOption;
+use ::error::{Result, Error, IoError, IoErrorKind};
+use super::UserId;
+
+
+/// User Details
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct User {
+    /// ID of the user.
+    pub id: String,
+    /// Primary e-mail address of the user.
+    pub email: String,
+    /// First name of the user.
+    pub first_name: String,
+    /// Last name of the user.
+    pub last_name: String,
+    /// Full name of the user.
+    pub full_name: String,
+    /// Company of the user.
+    pub company: String,
+    /// Indicates if the user is the employee of the account.
+    pub is_employee: bool,
+    /// Indicates if the user has confirmed the e-mail address.
+    pub is_confirmed: bool,
+    /// Date the user was created.
+    pub created_date: Option<DateTime<Utc>>,
+}
+
+
+impl User {
+    /// Get the `UserId` identifying the user by ID.
+    pub fn user_id(&self) -> UserId {
+        UserId::from_id(self.id.clone())
+    }
+
+    /// Construct user(s) from the decoded JSON value of the user or the user collection.
+    pub fn from_value(value: Value) -> Result<MultiOption<User>> {
+        // Check if we have one user or many
+        match value.find("odata.count") {
+            Some(_) => {
+                let mut users = Vec::new();
+                if let Some(list) = value.find("value").and_then(|v| v.as_array()) {
+                    for val in list {
+                        match User::from_json(val.clone()) {
+                            Ok(user) => users.push(user),
+                            Err(e) => return Err(e)
+                        };
+                    }
+                }
+
+                if users.is_empty() {
+                    Ok(MultiOption::None)
+                }
+                else {
+                    Ok(MultiOption::Many(users))
+                }
+            },
+            None => {
+                User::from_json(value)
+                    .map(|user| MultiOption::One(user))
+            }
+        }
+    }
+
+    /// Construct user from the decoded JSON value.
+    pub fn from_json(value: Value) -> Result<User> {
+        if let Some(object) = value.as_object() {
+            // Read ID
+            let id = match object.get("Id").and_then(|v| v.as_str()) {
+                Some(v) => v,
+                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "User.Id property is missing.")),
+            };
+
+            // Read e-mail
+            let email = match object.get("Email").and_then(|v| v.as_str()) {
+                Some(v) => v,
+                None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "User.Email property is missing.")),
+            };
+
+            // Read names and company (these properties are optional)
+            let first_name = object.get("FirstName")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+
+            let last_name = object.get("LastName")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+
+            let full_name = object.get("FullName")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+
+            let company = object.get("Company")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+
+            // Read flags (these properties are optional)
+            let is_employee = object.get("IsEmployee")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            let is_confirmed = object.get("IsConfirmed")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
+            // Read creation date (this property is optional)
+            let created_date = match object.get("DateCreated").and_then(|v| v.as_str()) {
+                Some(v) => match v.parse::<DateTime<Utc>>() {
+                    Ok(dt) => Some(dt),
+                    Err(err) => return Err(Error::from(err).context("User.DateCreated property is invalid"))
+                },
+                None => None,
+            };
+
+            // Construct the User struct
+            Ok(User {
+                id: id.to_owned(),
+                email: email.to_owned(),
+                first_name: first_name.to_owned(),
+                last_name: last_name.to_owned(),
+                full_name: full_name.to_owned(),
+                company: company.to_owned(),
+                is_employee: is_employee,
+                is_confirmed: is_confirmed,
+                created_date: created_date,
+            })
+        }
+        else {
+            Error::io_result(IoError::new(IoErrorKind::InvalidInput, "User can be constructed from JSON Object only."))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use ::api::MultiOption;
+    use super::User;
+
+    #[test]
+    fn user_from_json() {
+        let value = serde_json::from_str("{\"Id\":\"u-1\",\"Email\":\"jane@example.com\",\"FirstName\":\"Jane\",\"LastName\":\"Doe\",\"FullName\":\"Jane Doe\",\"Company\":\"ACME\",\"IsEmployee\":true,\"IsConfirmed\":true,\"DateCreated\":\"2018-10-01T10:20:30.000Z\"}").unwrap();
+        let user = User::from_json(value).unwrap();
+        assert_eq!(user.id, "u-1");
+        assert_eq!(user.email, "jane@example.com");
+        assert_eq!(user.first_name, "Jane");
+        assert_eq!(user.last_name, "Doe");
+        assert_eq!(user.full_name, "Jane Doe");
+        assert_eq!(user.company, "ACME");
+        assert!(user.is_employee);
+        assert!(user.is_confirmed);
+        assert!(user.created_date.is_some());
+    }
+
+    #[test]
+    fn user_from_minimal_json() {
+        let value = serde_json::from_str("{\"Id\":\"u-1\",\"Email\":\"jane@example.com\"}").unwrap();
+        let user = User::from_json(value).unwrap();
+        assert_eq!(user.first_name, "");
+        assert!(!user.is_employee);
+        assert_eq!(user.created_date, None);
+    }
+
+    #[test]
+    fn user_from_json_without_id() {
+        let value = serde_json::from_str("{\"Email\":\"jane@example.com\"}").unwrap();
+        assert!(User::from_json(value).is_err());
+    }
+
+    #[test]
+    fn users_from_collection() {
+        let value = serde_json::from_str("{\"odata.count\":2,\"value\":[{\"Id\":\"u-1\",\"Email\":\"a@example.com\"},{\"Id\":\"u-2\",\"Email\":\"b@example.com\"}]}").unwrap();
+        match User::from_value(value).unwrap() {
+            MultiOption::Many(users) => assert_eq!(users.iter().map(|u| u.id.as_str()).collect::<Vec<_>>(), vec!["u-1", "u-2"]),
+            other => panic!("Unexpected {:?}", other)
+        }
+    }
+
+    #[test]
+    fn users_from_empty_collection() {
+        let value = serde_json::from_str("{\"odata.count\":0,\"value\":[]}").unwrap();
+        assert!(User::from_value(value).unwrap().is_none());
+    }
+}
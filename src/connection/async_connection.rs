@@ -0,0 +1,158 @@
+use std::io::{Read, Write};
+use std::thread;
+use futures::Future;
+use futures::sync::oneshot;
+use hyper::method::Method;
+use hyper::header::Headers;
+use serde_json::Value;
+use ::error::{Result, Error};
+use ::api::items::{Path, Item};
+use super::Connection;
+
+
+/// The future resolved with the result of the asynchronous API call
+pub type AsyncResult<T> = Box<Future<Item = T, Error = Error> + Send>;
+
+
+/// Asynchronous wrapper of `Connection`
+///
+/// The methods return futures instead of blocking the caller. Each call is performed
+/// on the separate thread with the blocking `Connection` so the futures can be driven
+/// by any executor. The wrapper is available with `futures` feature enabled.
+///
+/// # Examples
+///
+/// ```ignore
+/// use futures::Future;
+/// use shapir::AsyncConnection;
+///
+/// // Lets assume we have the opened connection already
+/// let conn = AsyncConnection::new(conn);
+/// let data = conn.download(Path::Absolute(String::from("/my_folder/remote_file.txt")))
+///     .wait()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct AsyncConnection {
+	conn: Connection,
+}
+
+
+impl AsyncConnection {
+	/// Create the asynchronous wrapper of the opened `conn`.
+	pub fn new(conn: Connection) -> AsyncConnection {
+		AsyncConnection {
+			conn: conn,
+		}
+	}
+
+	/// Get the wrapped blocking connection.
+	pub fn connection(&self) -> &Connection {
+		&self.conn
+	}
+
+	/// Perform the call to the API which returns JSON. See `Connection::query_json()`.
+	pub fn query_json(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<Value>) -> AsyncResult<Value> {
+		let conn = self.conn.clone();
+		spawn(move || conn.query_json(method, uri, headers, body))
+	}
+
+	/// Download the whole content of the file at `path`. See `Items::download()`.
+	pub fn download(&self, path: Path) -> AsyncResult<Vec<u8>> {
+		let conn = self.conn.clone();
+		spawn(move || {
+			let mut data = Vec::new();
+			conn.items().download(path)
+				.and_then(|mut content| content.read_to_end(&mut data).map_err(Error::from))
+				.map(|_| data)
+		})
+	}
+
+	/// Download the content of the file at `path` into `out`. The future is resolved with
+	/// `out` and the amount of bytes copied. See `Items::download_to_writer()`.
+	pub fn download_to_writer<W>(&self, path: Path, mut out: W) -> AsyncResult<(W, u64)>
+	where W: Write + Send + 'static {
+		let conn = self.conn.clone();
+		spawn(move || {
+			conn.items().download_to_writer(path, &mut out)
+				.map(|copied| (out, copied))
+		})
+	}
+
+	/// Upload `data` into the folder identified by `parent` as the file `name` and return
+	/// the item created. See `Items::upload_from_reader()`.
+	pub fn upload(&self, parent: Path, name: String, data: Vec<u8>, overwrite: bool) -> AsyncResult<Item> {
+		let conn = self.conn.clone();
+		spawn(move || {
+			let size = data.len() as u64;
+			conn.items().upload_from_reader(parent, name, &mut &data[..], size, false, overwrite)
+		})
+	}
+}
+
+
+// Run `call` on the separate thread and resolve the future returned with its result
+fn spawn<T, F>(call: F) -> AsyncResult<T>
+where T: Send + 'static, F: FnOnce() -> Result<T> + Send + 'static {
+	let (tx, rx) = oneshot::channel();
+
+	thread::spawn(move || {
+		// The receiver can be dropped already if nobody waits for the result
+		let _ = tx.send(call());
+	});
+
+	Box::new(rx.then(|received| match received {
+		Ok(result) => result,
+		Err(_) => Err(Error::from("The asynchronous call is canceled")),
+	}))
+}
+
+
+#[cfg(test)]
+mod tests {
+	use futures::Future;
+	use hyper::method::Method;
+	use ::api::items::Path;
+	use ::mock::{MockServer, MockResponse};
+	use super::super::{Connection, ConnectionSettings};
+	use super::AsyncConnection;
+
+	fn connection(server: &MockServer) -> AsyncConnection {
+		AsyncConnection::new(Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url()))
+	}
+
+	#[test]
+	fn async_query_json() {
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "{\"Id\":\"fo-home\"}")]);
+
+		let value = connection(&server).query_json(Method::Get, "Items(home)".to_owned(), None, None)
+			.wait()
+			.unwrap();
+		assert_eq!(value.find("Id").and_then(|v| v.as_str()), Some("fo-home"));
+	}
+
+	#[test]
+	fn async_query_json_failed() {
+		let server = MockServer::start(vec![
+			MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}")]);
+
+		let err = connection(&server).query_json(Method::Get, "Items(missing)".to_owned(), None, None)
+			.wait()
+			.unwrap_err();
+		assert!(err.is_not_found());
+	}
+
+	#[test]
+	fn async_download() {
+		let storage = MockServer::start(vec![
+			MockResponse::new(200, "file content")]);
+		let server = MockServer::start(vec![
+			MockResponse::new(200, format!("{{\"DownloadUrl\":\"{}download?id=fi-1\"}}", storage.url()))]);
+
+		let data = connection(&server).download(Path::Id("fi-1".to_owned()))
+			.wait()
+			.unwrap();
+		assert_eq!(data, b"file content".to_vec());
+	}
+}
@@ -0,0 +1,141 @@
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde_json::Value;
+use ::error::{Result, Error, IoError, IoErrorKind, ServiceError};
+
+
+/// Authentication tokens obtained on connection
+///
+/// The token set can be exported from the opened `Connection` with `export_auth()`,
+/// stored and used later to restore the connection with `Connection::from_auth()`
+/// skipping the password authentication. The struct serializes into JSON with the same
+/// property names the OAuth token endpoint returns.
+///
+/// The tokens give the full access to the account so the caller is responsible
+/// for storing them securely.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SerializableAuth {
+	/// Subdomain of the account
+	pub subdomain: String,
+	/// Token type
+	pub token_type: String,
+	/// Access token
+	pub access_token: String,
+	/// Refresh token
+	pub refresh_token: String,
+}
+
+
+impl SerializableAuth {
+	/// Construct the token set from the decoded JSON value.
+	pub fn from_json(value: Value) -> Result<SerializableAuth> {
+		// Test if the value contains error
+		if let Some(ref error) = value.lookup("error") {
+			return Error::service_result(ServiceError::new(None, error.as_str().unwrap_or("Authentication failed")));
+		};
+
+		// Get auth values
+		let subdomain = match value.find("subdomain").and_then(|v| v.as_str()) {
+			Some(v) => v,
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Subdomain is missing."))
+		};
+
+		let token_type = match value.find("token_type").and_then(|v| v.as_str()) {
+			Some(v) => v,
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Token Type is missing."))
+		};
+
+		let access_token = match value.find("access_token").and_then(|v| v.as_str()) {
+			Some(v) => v,
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Access Token is missing."))
+		};
+
+		let refresh_token = match value.find("refresh_token").and_then(|v| v.as_str()) {
+			Some(v) => v,
+			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Refresh Token is missing."))
+		};
+
+		// Fill in auth struct
+		Ok(SerializableAuth {
+			subdomain: subdomain.to_string(),
+			token_type: token_type.to_string(),
+			access_token: access_token.to_string(),
+			refresh_token: refresh_token.to_string(),
+		})
+	}
+}
+
+
+// Serialize through the JSON Value representation
+impl Serialize for SerializableAuth {
+	fn serialize<S>(&self, serializer: &mut S) -> ::std::result::Result<(), S::Error>
+	where S: Serializer {
+		use serde_json::value::Map;
+		let mut object: Map<String, Value> = Map::new();
+
+		object.insert("subdomain".to_owned(), Value::String(self.subdomain.clone()));
+		object.insert("token_type".to_owned(), Value::String(self.token_type.clone()));
+		object.insert("access_token".to_owned(), Value::String(self.access_token.clone()));
+		object.insert("refresh_token".to_owned(), Value::String(self.refresh_token.clone()));
+
+		Value::Object(object).serialize(serializer)
+	}
+}
+
+
+// Deserialize through the JSON Value representation
+impl Deserialize for SerializableAuth {
+	fn deserialize<D>(deserializer: &mut D) -> ::std::result::Result<SerializableAuth, D::Error>
+	where D: Deserializer {
+		use serde::de::Error as DeError;
+
+		Value::deserialize(deserializer)
+			.and_then(|value| SerializableAuth::from_json(value)
+				.map_err(|err| D::Error::custom(err.to_string())))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::SerializableAuth;
+	use serde_json::{self, Value};
+	use ::error::Error;
+
+	#[test]
+	fn parse_auth_json() {
+		let data: Value = serde_json::from_str("{\"access_token\":\"new-access\",\"refresh_token\":\"new-refresh\",\"token_type\":\"bearer\",\"expires_in\":28800,\"appcp\":\"sharefile.com\",\"apicp\":\"sf-api.com\",\"subdomain\":\"tenant\"}").unwrap();
+		let auth = SerializableAuth::from_json(data).unwrap();
+		assert_eq!(auth.subdomain, "tenant");
+		assert_eq!(auth.access_token, "new-access");
+		assert_eq!(auth.refresh_token, "new-refresh");
+	}
+
+	#[test]
+	fn parse_auth_error_json() {
+		let data: Value = serde_json::from_str("{\"error\":\"invalid_grant\",\"error_description\":\"refresh token expired\"}").unwrap();
+		match SerializableAuth::from_json(data) {
+			Err(Error::Service(ref err)) => assert_eq!(err.message(), "invalid_grant"),
+			other => panic!("Expected service error but got {:?}", other),
+		};
+	}
+
+	#[test]
+	fn parse_auth_invalid_json() {
+		let data: Value = serde_json::from_str("{\"access_token\":42,\"refresh_token\":\"new-refresh\",\"token_type\":\"bearer\",\"subdomain\":\"tenant\"}").unwrap();
+		assert!(SerializableAuth::from_json(data).is_err());
+	}
+
+	#[test]
+	fn serialize_round_trip() {
+		let auth = SerializableAuth {
+			subdomain: "tenant".to_owned(),
+			token_type: "bearer".to_owned(),
+			access_token: "access".to_owned(),
+			refresh_token: "refresh".to_owned(),
+		};
+
+		let json = serde_json::to_string(&auth).unwrap();
+		let restored: SerializableAuth = serde_json::from_str(&json).unwrap();
+		assert_eq!(restored, auth);
+	}
+}
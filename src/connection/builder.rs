@@ -1,5 +1,5 @@
 use std::time::Duration;
-use ::Result;
+use ::error::{Result, Error, IoError, IoErrorKind};
 use super::ConnectionSettings;
 use super::Connection;
 
@@ -31,6 +31,24 @@ impl ConnectionBuilder {
 		self
 	}
 
+	/// Set `connect_timeout` setting
+	pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+		self.settings.connect_timeout = Some(timeout);
+		self
+	}
+
+	/// Set `max_retries` setting
+	pub fn max_retries(mut self, retries: u32) -> Self {
+		self.settings.max_retries = Some(retries);
+		self
+	}
+
+	/// Set `retry_backoff` setting
+	pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+		self.settings.retry_backoff = Some(backoff);
+		self
+	}
+
 	/// Set `subdomain` setting
 	pub fn subdomain<T>(mut self, subdomain: T) -> Self
 		where T: Into<String> {
@@ -52,6 +70,15 @@ impl ConnectionBuilder {
 		self
 	}
 
+	/// Set `authorization_code` setting. The `code` is the one ShareFile redirects the user
+	/// to `redirect_uri` with after granting the access; it is exchanged for the access
+	/// token on `connect()` instead of `username` and `password`.
+	pub fn authorization_code<T>(mut self, code: T, redirect_uri: T) -> Self
+		where T: Into<String> {
+		self.settings.authorization_code = Some((code.into(), redirect_uri.into()));
+		self
+	}
+
 	/// Set `client_id` setting
 	pub fn client_id<T>(mut self, client_id: T) -> Self
 		where T: Into<String> {
@@ -66,9 +93,146 @@ impl ConnectionBuilder {
 		self
 	}
 
+	/// Set `endpoint` setting
+	pub fn endpoint<T>(mut self, endpoint: T) -> Self
+		where T: Into<String> {
+		self.settings.endpoint = Some(endpoint.into());
+		self
+	}
+
+	/// Set `api_version` setting
+	pub fn api_version<T>(mut self, api_version: T) -> Self
+		where T: Into<String> {
+		self.settings.api_version = Some(api_version.into());
+		self
+	}
+
+	/// Set `auth_endpoint` setting
+	pub fn auth_endpoint<T>(mut self, auth_endpoint: T) -> Self
+		where T: Into<String> {
+		self.settings.auth_endpoint = Some(auth_endpoint.into());
+		self
+	}
+
+	/// Set `proxy` setting
+	pub fn proxy<T>(mut self, host: T, port: u16) -> Self
+		where T: Into<String> {
+		self.settings.proxy = Some((host.into(), port));
+		self
+	}
+
+	/// Set `proxy_auth` setting
+	pub fn proxy_auth<T>(mut self, proxy_auth: T) -> Self
+		where T: Into<String> {
+		self.settings.proxy_auth = Some(proxy_auth.into());
+		self
+	}
+
+	/// Set `user_agent` setting
+	pub fn user_agent<T>(mut self, user_agent: T) -> Self
+		where T: Into<String> {
+		self.settings.user_agent = Some(user_agent.into());
+		self
+	}
+
+	/// Set `danger_accept_invalid_certs` setting. If enabled TLS certificates of servers are
+	/// not verified at all so any certificate is accepted, including expired, self-signed and
+	/// issued for other hosts.
+	///
+	/// **This is dangerous.** The connection can be intercepted by anyone in the middle and
+	/// the credentials and data sent can be stolen. Enable it only for testing against servers
+	/// with self-signed certificates, like a StorageZone in the test environment, and never
+	/// in production.
+	pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+		self.settings.danger_accept_invalid_certs = accept;
+		self
+	}
+
+	/// Check all settings required for connecting are set. The error returned names
+	/// all settings missing. `username` and `password` are not required if
+	/// `authorization_code` is set.
+	pub fn validate(&self) -> Result<()> {
+		let by_code = self.settings.authorization_code.is_some();
+		if by_code && self.settings.password.is_some() {
+			return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Either password or authorization code should be set, not both"));
+		}
+
+		let required = [
+			("subdomain", self.settings.subdomain.is_some()),
+			("username", by_code || self.settings.username.is_some()),
+			("password", by_code || self.settings.password.is_some()),
+			("client_id", self.settings.client_id.is_some()),
+			("client_secret", self.settings.client_secret.is_some()),
+		];
+
+		let missing: Vec<&str> = required.iter()
+			.filter(|&&(_, present)| !present)
+			.map(|&(name, _)| name)
+			.collect();
+
+		if missing.is_empty() {
+			Ok(())
+		}
+		else {
+			Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("Required settings are missing: {}", missing.join(", "))))
+		}
+	}
+
 	/// Creates the configured instance of the `Connection` and tries to connect.
 	pub fn connect(self) -> Result<Connection> {
-		Connection::configured(self.settings)
-			.connect()
+		match self.validate() {
+			Ok(_) => Connection::configured(self.settings).connect(),
+			Err(e) => Err(e)
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::ConnectionBuilder;
+
+	#[test]
+	fn validate_complete() {
+		let builder = ConnectionBuilder::new()
+			.subdomain("tenant")
+			.username("user@mail.com")
+			.password("secret")
+			.client_id("client-id")
+			.client_secret("client-secret");
+		assert!(builder.validate().is_ok());
+	}
+
+	#[test]
+	fn validate_missing() {
+		let builder = ConnectionBuilder::new()
+			.subdomain("tenant")
+			.username("user@mail.com")
+			.client_id("client-id");
+
+		let err = builder.validate().unwrap_err();
+		assert_eq!(err.to_string(), "Required settings are missing: password, client_secret");
+
+		let err = builder.connect().err().unwrap();
+		assert_eq!(err.to_string(), "Required settings are missing: password, client_secret");
+	}
+
+	#[test]
+	fn danger_accept_invalid_certs() {
+		assert!(!ConnectionBuilder::new().settings.danger_accept_invalid_certs);
+		assert!(ConnectionBuilder::new().danger_accept_invalid_certs(true).settings.danger_accept_invalid_certs);
+	}
+
+	#[test]
+	fn validate_authorization_code() {
+		let builder = ConnectionBuilder::new()
+			.subdomain("tenant")
+			.authorization_code("code", "https://app.example.com/callback")
+			.client_id("client-id")
+			.client_secret("client-secret");
+		assert!(builder.validate().is_ok());
+
+		let err = builder.password("secret").validate().unwrap_err();
+		assert_eq!(err.to_string(), "Either password or authorization code should be set, not both");
 	}
 }
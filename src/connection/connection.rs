@@ -1,60 +1,25 @@
 use std::io::Read;
-use std::sync::Arc;
-use hyper::client::{Client, Body};
+use std::cmp;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use hyper::client::{Client, Body, ProxyConfig};
+use hyper::client::pool::Pool;
+use hyper::net::{HttpsConnector, HttpConnector, SslClient, Openssl, OpensslClient};
 use hyper::client::response::Response;
 use hyper::method::Method;
-use hyper::header::{Headers, ContentType, Authorization, Bearer};
+use hyper::status::StatusCode;
+use hyper::header::{Headers, ContentType, Authorization, Bearer, UserAgent, AcceptEncoding, Encoding, qitem};
 use hyper::mime::{Mime, TopLevel, SubLevel};
 use serde_json::{self, ser, Value};
-use ::error::{Result, Error, IoError, IoErrorKind, NetworkError, ServiceError};
-use super::{ConnectionSettings, ConnectionBuilder, ConnectionHelper};
+use ::error::{Result, Error, IoError, IoErrorKind, ServiceError};
+use ::odata::Parameters;
+use super::{ConnectionSettings, ConnectionBuilder, ConnectionHelper, SerializableAuth};
+use super::connector::TimeoutConnector;
 
 
-#[derive(Debug, Clone)]
-struct AuthData {
-	subdomain: String,
-	token_type: String,
-	access_token: String,
-	refresh_token: String,
-}
-
-impl AuthData {
-	fn parse_value(value: Value) -> Result<AuthData> {
-		// Test if the value contains error
-		if let Some(ref error) = value.lookup("error") {
-			return Error::service_result(ServiceError::new(None, error.as_str().unwrap()));
-		};
-
-		// Get auth values
-		let subdomain = match value.find("subdomain") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Subdomain is missing."))
-		};
-
-		let token_type = match value.find("token_type") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Token Type is missing."))
-		};
-
-		let access_token = match value.find("access_token") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Access Token is missing."))
-		};
-
-		let refresh_token = match value.find("refresh_token") {
-			Some(v) => v.as_str().unwrap(),
-			None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Auth Refresh Token is missing."))
-		};
-
-		// Fill in auth struct
-		Ok(AuthData {
-			subdomain: subdomain.to_string(),
-			token_type: token_type.to_string(),
-			access_token: access_token.to_string(),
-			refresh_token: refresh_token.to_string(),
-		})
-	}
-}
+// The longest delay before the retry computed from `retry_backoff` setting
+const MAX_RETRY_DELAY_SECS: u64 = 60;
 
 
 /// Authentication and communication facility  
@@ -85,7 +50,7 @@ impl AuthData {
 pub struct Connection {
 	client: Arc<Client>,
 	settings: ConnectionSettings,
-	auth: Option<AuthData>,
+	auth: Option<Arc<RwLock<SerializableAuth>>>,
 	endpoint: String,
 }
 
@@ -97,34 +62,210 @@ impl Connection {
 		ConnectionBuilder::new()
 	}
 
+	/// Create the instance of `ConnectionBuilder` populated from environment variables.
+	/// `SHAREFILE_SUBDOMAIN`, `SHAREFILE_USERNAME`, `SHAREFILE_PASSWORD`,
+	/// `SHAREFILE_CLIENT_ID` and `SHAREFILE_CLIENT_SECRET` are required.
+	/// `SHAREFILE_READ_TIMEOUT` and `SHAREFILE_WRITE_TIMEOUT` are optional
+	/// and set in seconds.
+	pub fn from_env() -> Result<ConnectionBuilder> {
+		use std::env;
+
+		fn required(name: &str) -> Result<String> {
+			match env::var(name) {
+				Ok(value) => Ok(value),
+				Err(_) => Error::io_result(IoError::new(IoErrorKind::InvalidInput, format!("{} environment variable is required", name)))
+			}
+		}
+
+		fn timeout(name: &str, context: &'static str) -> Result<Option<Duration>> {
+			match env::var(name) {
+				Ok(value) => match value.trim().parse::<u64>() {
+					Ok(secs) => Ok(Some(Duration::from_secs(secs))),
+					Err(err) => Err(Error::from(err).context(context))
+				},
+				Err(_) => Ok(None)
+			}
+		}
+
+		let subdomain = match required("SHAREFILE_SUBDOMAIN") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
+		};
+
+		let username = match required("SHAREFILE_USERNAME") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
+		};
+
+		let password = match required("SHAREFILE_PASSWORD") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
+		};
+
+		let client_id = match required("SHAREFILE_CLIENT_ID") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
+		};
+
+		let client_secret = match required("SHAREFILE_CLIENT_SECRET") {
+			Ok(v) => v,
+			Err(e) => return Err(e)
+		};
+
+		let mut builder = ConnectionBuilder::new()
+			.subdomain(subdomain)
+			.username(username)
+			.password(password)
+			.client_id(client_id)
+			.client_secret(client_secret);
+
+		builder = match timeout("SHAREFILE_READ_TIMEOUT", "SHAREFILE_READ_TIMEOUT environment variable must be the number of seconds") {
+			Ok(Some(timeout)) => builder.read_timeout(timeout),
+			Ok(None) => builder,
+			Err(e) => return Err(e)
+		};
+
+		builder = match timeout("SHAREFILE_WRITE_TIMEOUT", "SHAREFILE_WRITE_TIMEOUT environment variable must be the number of seconds") {
+			Ok(Some(timeout)) => builder.write_timeout(timeout),
+			Ok(None) => builder,
+			Err(e) => return Err(e)
+		};
+
+		Ok(builder)
+	}
+
 	/// Create the configured `Connection` using given settings.
 	pub fn configured(settings: ConnectionSettings) -> Connection {
-		let mut client = Client::new();
+		let mut client = if settings.danger_accept_invalid_certs {
+			// The plain OpenSSL context does not verify certificates
+			Connection::client_with_ssl(&settings, Openssl::default())
+		}
+		else {
+			Connection::client_with_ssl(&settings, OpensslClient::default())
+		};
 
 		// Set I/O operations timeouts
 		client.set_read_timeout(settings.read_timeout);
 		client.set_write_timeout(settings.write_timeout);
 
 		// Construct connection instance
+		Connection::with_client(Arc::new(client), settings)
+	}
+
+	// Create the client establishing TLS connections with `ssl`
+	fn client_with_ssl<S>(settings: &ConnectionSettings, ssl: S) -> Client
+		where S: SslClient + Send + Sync + 'static {
+		match (&settings.proxy, settings.connect_timeout) {
+			(&Some((ref host, port)), _) => Client::with_proxy_config(ProxyConfig(host.clone(), port, ssl)),
+			(&None, Some(timeout)) => {
+				let connector = HttpsConnector::with_connector(ssl, TimeoutConnector::new(timeout));
+				Client::with_connector(Pool::with_connector(Default::default(), connector))
+			},
+			(&None, None) => {
+				let connector = HttpsConnector::with_connector(ssl, HttpConnector);
+				Client::with_connector(Pool::with_connector(Default::default(), connector))
+			}
+		}
+	}
+
+	/// Create the configured `Connection` which sends requests with the `client` given.
+	/// The client can be shared by many connections so they reuse its connection pool
+	/// and TLS configuration. The client is used as it is so `read_timeout`, `write_timeout`,
+	/// `connect_timeout`, `proxy` and `danger_accept_invalid_certs` settings are not applied and should be configured
+	/// on the client if required. Changes made to the client affect all connections
+	/// sharing it.
+	pub fn with_client(client: Arc<Client>, settings: ConnectionSettings) -> Connection {
 		Connection {
-			client: Arc::new(client),
+			client: client,
 			settings: settings,
 			auth: None,
 			endpoint: "".to_string(),
 		}
 	}
 
-	/// Connect to ShareFile REST API. Behind the scene it does login and obtains authentication tokens
-	/// used in all API requests to the API.
-	pub fn connect(mut self) -> Result<Connection> {
+	/// Create the opened `Connection` using given settings and the authentication tokens
+	/// exported from another connection with `export_auth()`. No authentication request
+	/// is made so the tokens are expected to be valid.
+	pub fn from_auth(settings: ConnectionSettings, auth: SerializableAuth) -> Connection {
+		let mut conn = Connection::configured(settings);
+		conn.endpoint = conn.settings.api_endpoint(&auth.subdomain);
+		conn.auth = Some(Arc::new(RwLock::new(auth)));
+		conn
+	}
+
+	/// Build the URL of ShareFile consent page the user is sent to for granting the access
+	/// to the application. After that the user is redirected to `redirect_uri` with `code`
+	/// and `state` query parameters, the code is then passed to
+	/// `ConnectionBuilder::authorization_code()`.
+	pub fn authorize_url(subdomain: &str, client_id: &str, redirect_uri: &str, state: &str) -> String {
 		use url::form_urlencoded;
 
+		let query: String = form_urlencoded::Serializer::new(String::new())
+			.append_pair("response_type", "code")
+			.append_pair("client_id", client_id)
+			.append_pair("redirect_uri", redirect_uri)
+			.append_pair("state", state)
+			.finish();
+
+		format!("https://{}.sharefile.com/oauth/authorize?{}", subdomain, query)
+	}
+
+	/// Connect to ShareFile REST API. Behind the scene it does login and obtains authentication tokens
+	/// used in all API requests to the API. The `authorization_code` grant is used if the code
+	/// is set, otherwise the `password` grant is used.
+	pub fn connect(mut self) -> Result<Connection> {
 		// Prepare authentication request body and URL
 		let subdomain = match &self.settings.subdomain {
 			&Some(ref v) => v.clone(),
 			&None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Subdomain is required"))
 		};
 
+		let form_data = match self.token_form() {
+			Ok(v) => v,
+			Err(err) => return Err(err)
+		};
+
+		// Try to authenticate on ShareFile
+		match self.request_token(&subdomain, form_data) {
+			Ok(data) => {
+				self.endpoint = self.settings.api_endpoint(&data.subdomain);
+				self.auth = Some(Arc::new(RwLock::new(data)));
+				Ok(self)
+			},
+			Err(err) => {
+				Err(err)
+			}
+		}
+	}
+
+	// Build the OAuth token request form of the grant configured
+	fn token_form(&self) -> Result<String> {
+		use url::form_urlencoded;
+
+		let client_id = match &self.settings.client_id {
+			&Some(ref v) => v.clone(),
+			&None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Client ID is required"))
+		};
+
+		let client_secret = match &self.settings.client_secret {
+			&Some(ref v) => v.clone(),
+			&None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Client Secret is required"))
+		};
+
+		if let Some((ref code, ref redirect_uri)) = self.settings.authorization_code {
+			if self.settings.password.is_some() {
+				return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Either password or authorization code should be set, not both"));
+			}
+
+			return Ok(form_urlencoded::Serializer::new(String::new())
+				.append_pair("grant_type", "authorization_code")
+				.append_pair("code", code)
+				.append_pair("redirect_uri", redirect_uri)
+				.append_pair("client_id", &client_id)
+				.append_pair("client_secret", &client_secret)
+				.finish());
+		}
+
 		let username = match &self.settings.username {
 			&Some(ref v) => v.clone(),
 			&None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Username is required"))
@@ -135,6 +276,77 @@ impl Connection {
 			&None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Password is required"))
 		};
 
+		Ok(form_urlencoded::Serializer::new(String::new())
+			.append_pair("grant_type", "password")
+			.append_pair("client_id", &client_id)
+			.append_pair("client_secret", &client_secret)
+			.append_pair("username", &username)
+			.append_pair("password", &password)
+			.finish())
+	}
+
+	/// Obtain the new access token using the refresh token received on `connect()`
+	/// and store it in the connection. The tokens are shared by the connection clones
+	/// so they all use the new token.
+	pub fn refresh(&mut self) -> Result<()> {
+		let auth = match self.auth {
+			Some(ref auth) => auth.clone(),
+			None => return Error::io_result(IoError::new(IoErrorKind::PermissionDenied, "Not authenticated"))
+		};
+
+		let current = Connection::read_auth(&auth);
+		self.refresh_shared(&auth, &current.access_token)
+			.map(|_| ())
+	}
+
+	// Get the copy of the tokens shared
+	fn read_auth(auth: &RwLock<SerializableAuth>) -> SerializableAuth {
+		match auth.read() {
+			Ok(auth) => auth.clone(),
+			Err(poisoned) => poisoned.into_inner().clone()
+		}
+	}
+
+	// Refresh the shared tokens unless they are refreshed already after `access_token`
+	// was rejected, e.g. by another clone of the connection. Refresh tokens are rotated
+	// so the refresh is done under the lock and the stored tokens are replaced.
+	fn refresh_shared(&self, auth: &RwLock<SerializableAuth>, access_token: &str) -> Result<SerializableAuth> {
+		let mut stored = match auth.write() {
+			Ok(stored) => stored,
+			Err(poisoned) => poisoned.into_inner()
+		};
+
+		if stored.access_token != access_token {
+			return Ok(stored.clone());
+		}
+
+		match self.refresh_auth(&stored) {
+			Ok(data) => {
+				*stored = data.clone();
+				Ok(data)
+			},
+			Err(err) => Err(err)
+		}
+	}
+
+	/// Log out expiring the current session on ShareFile. The authentication tokens are
+	/// removed from the connection even if the request fails so all further API calls fail
+	/// with "Not authenticated" error. The tokens exported with `export_auth()` or held by
+	/// the connection clones are not valid after successful logout.
+	pub fn logout(&mut self) -> Result<()> {
+		let result = self.query_string(Method::Get, String::from("Sessions/Logout"), None, None)
+			.map(|_| ());
+
+		self.auth = None;
+		self.endpoint = String::new();
+
+		result
+	}
+
+	// Request the new token set using the refresh token of `auth`
+	fn refresh_auth(&self, auth: &SerializableAuth) -> Result<SerializableAuth> {
+		use url::form_urlencoded;
+
 		let client_id = match &self.settings.client_id {
 			&Some(ref v) => v.clone(),
 			&None => return Error::io_result(IoError::new(IoErrorKind::InvalidInput, "Client ID is required"))
@@ -146,58 +358,77 @@ impl Connection {
 		};
 
 		let form_data: String = form_urlencoded::Serializer::new(String::new())
-			.append_pair("grant_type", "password")
+			.append_pair("grant_type", "refresh_token")
+			.append_pair("refresh_token", &auth.refresh_token)
 			.append_pair("client_id", &client_id)
 			.append_pair("client_secret", &client_secret)
-			.append_pair("username", &username)
-			.append_pair("password", &password)
 			.finish();
+
+		self.request_token(&auth.subdomain, form_data)
+	}
+
+	// POST the form to the OAuth token endpoint and parse the token set returned
+	fn request_token(&self, subdomain: &str, form_data: String) -> Result<SerializableAuth> {
 		let form_data_len = form_data.len();
 
-		let url = match super::url::to_url(format!("https://{}.sharefile.com/oauth/token", subdomain)) {
+		let url = match super::url::to_url(self.settings.token_endpoint(subdomain)) {
 			Ok(v) => v,
 			Err(err) => return Error::url_result(err)
 		};
 
-		// Try to authenticate on ShareFile
 		let response = self.client.request(Method::Post, url)
+			.headers(self.request_headers(None))
 			.header(ContentType(Mime(TopLevel::Application, SubLevel::WwwFormUrlEncoded, vec![])))
 			.body(Body::BufBody(&form_data.into_bytes()[..], form_data_len))
 			.send();
 
-		let mut response = match response {
-			Ok(response) => response,
-			Err(err) => return Error::network_result(err)
-		};
+		match response {
+			Ok(response) => Connection::parse_token_response(response),
+			Err(err) => Error::network_result(err)
+		}
+	}
 
-		// Parse response into JSON Value and then into AuthData
+	// Parse the OAuth token endpoint response into JSON Value and then into SerializableAuth.
+	// Gateways can answer with HTML pages so the body is not expected to be JSON always.
+	fn parse_token_response<R: Read>(mut response: R) -> Result<SerializableAuth> {
 		let mut json = String::new();
-		response.read_to_string(&mut json).unwrap();
-
-		let value = match serde_json::from_str(&json) {
-			Ok(v) => v,
-			Err(err) => {
-				let mut s = err.to_string();
-				if json.contains("<head") {
-					s.push_str("\n");
-					s.push_str(&json);
-				}
-				return Error::other_result(err.to_string())
-			}
-		};
+		if let Err(err) = response.read_to_string(&mut json) {
+			return Error::io_result(err);
+		}
 
-		match AuthData::parse_value(value) {
-			Ok(data) => {
-				self.endpoint = format!("https://{}.sf-api.com/sf/v3/", data.subdomain);
-				self.auth = Some(data);
-				Ok(self)
-			},
-			Err(err) => {
-				Err(err)
-			}
+		match serde_json::from_str(&json) {
+			Ok(value) => SerializableAuth::from_json(value),
+			Err(err) => Error::json_result(err)
 		}
 	}
 
+	/// Export the authentication tokens for restoring the connection later with
+	/// `Connection::from_auth()`. Returns `None` if the connection is not opened yet.
+	///
+	/// The tokens give the full access to the account so the caller is responsible
+	/// for storing them securely.
+	pub fn export_auth(&self) -> Option<SerializableAuth> {
+		self.auth.as_ref().map(|auth| Connection::read_auth(auth))
+	}
+
+	/// Get the subdomain of the account authenticated or `None` if the connection
+	/// is not opened yet.
+	pub fn subdomain(&self) -> Option<String> {
+		self.export_auth().map(|auth| auth.subdomain)
+	}
+
+	/// Get the access token used in API calls or `None` if the connection is not opened yet.
+	/// The token changes when it's refreshed.
+	pub fn access_token(&self) -> Option<String> {
+		self.export_auth().map(|auth| auth.access_token)
+	}
+
+	/// Get the API endpoint all API calls are made against. The endpoint is empty
+	/// if the connection is not opened yet.
+	pub fn endpoint(&self) -> &str {
+		&self.endpoint
+	}
+
 	/// Perform the low-level custom HTTP request. Hyper's `Response` is returned on success.
 	pub fn custom_request(&self, method: Method, url: String, headers: Option<Headers>, body: Option<&[u8]>) -> Result<Response> {
 		// Parse URL string into the internal representation
@@ -206,34 +437,42 @@ impl Connection {
 			Err(err) => return Error::url_result(err)
 		};
 
-		// Build request
-		let mut request = self.client.request(method, url);
-
-		if let Some(headers) = headers {
-			request = request.headers(headers);
-		}
+		// Build and send request
+		self.send_with_retries(&method, || {
+			let mut request = self.client.request(method.clone(), url.clone())
+				.headers(self.request_headers(headers.clone()));
 
-		if let Some(body) = body {
-			if body.len() > 0 {
-				request = request.body(Body::BufBody(body, body.len()));
+			if let Some(body) = body {
+				if body.len() > 0 {
+					request = request.body(Body::BufBody(body, body.len()));
+				}
 			}
-		}
 
-		// .. send and unwrap to string
-		match request.send() {
-			Ok(response) => Ok(response),
-			Err(err) => Error::network_result(err)
-		}
+			request.send()
+		})
 	}
 
 	/// Perform the call to the API. Hyper's `Response` is returned on success.
+	///
+	/// If the API rejects the access token with `401 Unauthorized` the token is refreshed
+	/// and the request is repeated once. The refreshed tokens are stored in the connection
+	/// and its clones so further calls use them.
+	///
+	/// The compressed response is requested unless `headers` contain `Accept-Encoding` so
+	/// the body should be read with `ConnectionHelper::read_body()`.
 	pub fn query(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<String>) -> Result<Response> {
-		if let Some(ref auth) = self.auth {
-			// Parse URL string into the internal representation
-			let url = match super::url::to_url(format!("{}{}", self.endpoint, uri)) {
-				Ok(v) => v,
-				Err(err) => return Error::url_result(err)
-			};
+		if let Some(ref shared) = self.auth {
+			let auth = Connection::read_auth(shared);
+
+			// Build the full URL of the API call
+			let url = format!("{}{}", self.endpoint, uri);
+
+			// Ask for the compressed response
+			let mut headers = headers.unwrap_or(Headers::new());
+			if !headers.has::<AcceptEncoding>() {
+				headers.set(AcceptEncoding(vec![ qitem(Encoding::Gzip), qitem(Encoding::Deflate) ]));
+			}
+			let headers = Some(headers);
 
 			// Unwrap body so it lives long enough
 			let body = match body {
@@ -241,12 +480,39 @@ impl Connection {
 				None => "".to_string()
 			};
 
-			// Build request
-			let mut request = self.client.request(method, url);
+			let response = match self.authorized_request(&auth, method.clone(), url.clone(), headers.clone(), &body) {
+				Ok(response) => response,
+				Err(err) => return Err(err)
+			};
 
-			if let Some(headers) = headers {
-				request = request.headers(headers);
+			// Refresh the expired token and try once again
+			if response.status == StatusCode::Unauthorized {
+				match self.refresh_shared(shared, &auth.access_token) {
+					Ok(auth) => self.authorized_request(&auth, method, url, headers, &body),
+					Err(_) => Ok(response)
+				}
 			}
+			else {
+				Ok(response)
+			}
+		}
+		else {
+			Error::io_result(IoError::new(IoErrorKind::PermissionDenied, "Not authenticated"))
+		}
+	}
+
+	// Send the API request authorized with the access token of `auth`
+	fn authorized_request(&self, auth: &SerializableAuth, method: Method, url: String, headers: Option<Headers>, body: &str) -> Result<Response> {
+		// Parse URL string into the internal representation
+		let url = match super::url::to_url(url) {
+			Ok(v) => v,
+			Err(err) => return Error::url_result(err)
+		};
+
+		// Build and send request
+		self.send_with_retries(&method, || {
+			let mut request = self.client.request(method.clone(), url.clone())
+				.headers(self.request_headers(headers.clone()));
 
 			request = request.header(Authorization(Bearer { token: auth.access_token.to_owned() }));
 
@@ -254,33 +520,111 @@ impl Connection {
 				request = request.body(Body::BufBody(body.as_bytes(), body.len()));
 			}
 
-			// .. send and unwrap to string
-			match request.send() {
-				Ok(response) => Ok(response),
-				Err(err) => Error::network_result(err)
-			}
+			request.send()
+		})
+	}
+
+	// Add headers sent with each request to `headers` given
+	fn request_headers(&self, headers: Option<Headers>) -> Headers {
+		let mut headers = headers.unwrap_or(Headers::new());
+
+		let user_agent = match self.settings.user_agent {
+			Some(ref user_agent) => user_agent.clone(),
+			None => format!("shapir/{}", env!("CARGO_PKG_VERSION"))
+		};
+		headers.set(UserAgent(user_agent));
+
+		if let Some(ref proxy_auth) = self.settings.proxy_auth {
+			headers.set_raw("Proxy-Authorization", vec![proxy_auth.clone().into_bytes()]);
 		}
-		else {
-			Error::io_result(IoError::new(IoErrorKind::PermissionDenied, "Not authenticated"))
+
+		headers
+	}
+
+	// Send the request built by `send` and repeat it while the response status is `429` or `5XX`.
+	// Only idempotent requests are repeated and the number of retries is limited by
+	// `max_retries` setting. The delay before the retry is taken from `Retry-After` header
+	// or computed from `retry_backoff` setting.
+	fn send_with_retries<F>(&self, method: &Method, send: F) -> Result<Response>
+	where F: Fn() -> ::hyper::Result<Response> {
+		let retries = match method {
+			&Method::Get | &Method::Head | &Method::Delete => self.settings.max_retries.unwrap_or(0),
+			_ => 0
+		};
+		let backoff = self.settings.retry_backoff.unwrap_or(Duration::from_secs(1));
+		let mut attempt = 0;
+
+		loop {
+			let response = match send() {
+				Ok(response) => response,
+				Err(err) => return Error::network_result(err)
+			};
+
+			let retryable = response.status == StatusCode::TooManyRequests || response.status.is_server_error();
+			if !retryable || attempt >= retries {
+				return Ok(response);
+			}
+
+			// Wait and try once again
+			let delay = ConnectionHelper::retry_after(&response.headers)
+				.unwrap_or(Connection::retry_delay(backoff, attempt));
+			thread::sleep(delay);
+			attempt += 1;
 		}
 	}
 
+	// Compute the delay before the retry doubling `backoff` on each attempt. The delay is
+	// capped so large `max_retries` or `retry_backoff` do not overflow.
+	fn retry_delay(backoff: Duration, attempt: u32) -> Duration {
+		let max = cmp::max(backoff, Duration::from_secs(MAX_RETRY_DELAY_SECS));
+
+		2u32.checked_pow(cmp::min(attempt, 31))
+			.and_then(|factor| backoff.checked_mul(factor))
+			.map_or(max, |delay| cmp::min(delay, max))
+	}
+
 	/// Perform the call to the API. Response body is returned on success.
 	pub fn query_string(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<String>) -> Result<String> {
+		self.query_with_headers(method, uri, headers, body)
+			.map(|(data, _)| data)
+	}
+
+	/// Perform the call to the API. Response body and headers are returned on success.
+	/// Use `RateLimit::from_headers` to read rate limiting information from the headers.
+	pub fn query_with_headers(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<String>) -> Result<(String, Headers)> {
+		self.query_full(method, uri, headers, body)
+			.map(|(_, headers, data)| (data, headers))
+	}
+
+	/// Perform the call to the API. Response status, headers and body are returned on success,
+	/// so headers like `ETag` or `Last-Modified` can be read.
+	pub fn query_full(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<String>) -> Result<(StatusCode, Headers, String)> {
 		self.query(method, uri, headers, body)
 			.and_then(|mut response| {
-				if !response.status.is_server_error() {
-					let mut data = String::new();
-					response.read_to_string(&mut data)
-						.and(Ok(data))
-						.or(Error::other_result("Failed to read response body"))
+				let data = match ConnectionHelper::read_body(&response.headers.clone(), &mut response) {
+					Ok(data) => data,
+					Err(_) => return Error::other_result("Failed to read response body")
+				};
+
+				if response.status.is_success() {
+					Ok((response.status, response.headers.clone(), data))
 				}
 				else {
-					Error::network_result(NetworkError::from(IoError::new(IoErrorKind::Other, format!("API request failed with status {}", response.status))))
+					Err(Connection::response_error(response.status, &data))
 				}
 			})
 	}
 
+	// Construct the error of the failed response with `status` and the body `data`
+	fn response_error(status: StatusCode, data: &str) -> Error {
+		// Failed responses can contain JSON with the error details
+		let error = serde_json::from_str::<Value>(data).ok()
+			.and_then(|value| ServiceError::from_json(&value))
+			.unwrap_or(ServiceError::new(None, format!("API request failed with status {}", status)));
+
+		Error::from(error.with_status(status))
+	}
+
 	/// Perform the call to the API which returns JSON. JSON Value is returned on success.
 	pub fn query_json(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<Value>) -> Result<Value> {
 		let body = body.map(|ref value| ser::to_string(value).unwrap());
@@ -294,6 +638,59 @@ impl Connection {
 			})
 	}
 
+	/// Perform the call to the API which returns JSON like `query_json()` does but parse
+	/// the response body while it's read so the whole body is never held in memory as
+	/// the string. That is the better choice for very large responses like listings
+	/// of huge folders.
+	pub fn query_json_stream(&self, method: Method, uri: String, headers: Option<Headers>, body: Option<Value>) -> Result<Value> {
+		let body = body.map(|ref value| ser::to_string(value).unwrap());
+		let headers = headers.or(Some(ConnectionHelper::json_headers()));
+
+		self.query(method, uri, headers, body)
+			.and_then(|mut response| {
+				let status = response.status;
+				let headers = response.headers.clone();
+
+				if status.is_success() {
+					let reader = match ConnectionHelper::body_reader(&headers, &mut response) {
+						Ok(reader) => reader,
+						Err(_) => return Error::other_result("Failed to read response body")
+					};
+
+					serde_json::from_reader(reader)
+						.or_else(|err| Error::json_result(err)) // Return JSON parsing error
+						.and_then(|data| Error::from_json(data)) // Try to parse the error from the response JSON or just pass it through
+				}
+				else {
+					match ConnectionHelper::read_body(&headers, &mut response) {
+						Ok(data) => Err(Connection::response_error(status, &data)),
+						Err(_) => Error::other_result("Failed to read response body")
+					}
+				}
+			})
+	}
+
+	/// Perform the call to the API which returns JSON with the query `parameters` appended to
+	/// `uri` relative to the endpoint, e.g. `Zones` or `Groups(id)/Contacts`. JSON Value is
+	/// returned on success.
+	///
+	/// The method is the way to access API Entities which are not implemented in the SDK.
+	pub fn api_json(&self, method: Method, uri: &str, parameters: Option<Parameters>, body: Option<Value>) -> Result<Value> {
+		let parameters = parameters.map_or(String::new(), |p| p.to_string());
+
+		let uri = if parameters.is_empty() {
+			uri.to_owned()
+		}
+		else if uri.contains('?') {
+			format!("{}&{}", uri, parameters)
+		}
+		else {
+			format!("{}?{}", uri, parameters)
+		};
+
+		self.query_json(method, uri, None, body)
+	}
+
 	/// Get [Items](http://api.sharefile.com/rest/docs/resource.aspx?name=Items) API Entity.
 	pub fn items(&self) -> ::api::items::Items {
 		::api::items::Items::new(self.clone())
@@ -309,4 +706,585 @@ impl Connection {
 		::api::shares::Shares::new(self.clone())
 	}
 
+	/// Get [Users](http://api.sharefile.com/rest/docs/resource.aspx?name=Users) API Entity.
+	pub fn users(&self) -> ::api::users::Users {
+		::api::users::Users::new(self.clone())
+	}
+
+}
+
+
+
+// Construct connections bypassing authentication in tests
+#[cfg(test)]
+impl Connection {
+	pub fn authenticated<T: Into<String>>(settings: ConnectionSettings, subdomain: T, access_token: T, endpoint: T) -> Connection {
+		let mut conn = Connection::configured(settings);
+		conn.auth = Some(Arc::new(RwLock::new(SerializableAuth {
+			subdomain: subdomain.into(),
+			token_type: "bearer".to_string(),
+			access_token: access_token.into(),
+			refresh_token: "".to_string(),
+		})));
+		conn.endpoint = endpoint.into();
+		conn
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::TcpListener;
+	use std::thread;
+	use std::time::Duration;
+	use hyper::method::Method;
+	use hyper::status::StatusCode;
+	use ::error::{Error, IoErrorKind};
+	use ::mock::{MockServer, MockResponse};
+	use super::super::{ConnectionSettings, SerializableAuth};
+	use super::Connection;
+
+	fn retry_settings(retries: u32, backoff: Duration) -> ConnectionSettings {
+		let mut settings = ConnectionSettings::default();
+		settings.max_retries = Some(retries);
+		settings.retry_backoff = Some(backoff);
+		settings
+	}
+
+	#[test]
+	fn response_error_gateway_json() {
+		let error = Connection::response_error(StatusCode::BadRequest, "{\"code\":400,\"message\":\"bad\"}");
+		assert_eq!(error.to_string(), "bad");
+		assert_eq!(error.status(), Some(StatusCode::BadRequest));
+
+		let error = Connection::response_error(StatusCode::InternalServerError, "{\"code\":\"InternalError\",\"message\":\"boom\"}");
+		assert_eq!(error.to_string(), "InternalError: boom");
+		assert_eq!(error.service_code(), Some("InternalError"));
+
+		let error = Connection::response_error(StatusCode::BadGateway, "{\"code\":502}");
+		assert_eq!(error.to_string(), "API request failed with status 502 Bad Gateway");
+	}
+
+	#[test]
+	fn retry_delay_is_capped() {
+		let backoff = Duration::from_millis(500);
+		assert_eq!(Connection::retry_delay(backoff, 0), Duration::from_millis(500));
+		assert_eq!(Connection::retry_delay(backoff, 3), Duration::from_secs(4));
+		assert_eq!(Connection::retry_delay(backoff, 7), Duration::from_secs(60));
+		assert_eq!(Connection::retry_delay(backoff, 40), Duration::from_secs(60));
+		assert_eq!(Connection::retry_delay(backoff, u32::max_value()), Duration::from_secs(60));
+
+		let backoff = Duration::from_secs(u64::max_value());
+		assert_eq!(Connection::retry_delay(backoff, 0), backoff);
+		assert_eq!(Connection::retry_delay(backoff, 1), backoff);
+	}
+
+	#[test]
+	fn from_env() {
+		use std::env;
+
+		let vars = [
+			("SHAREFILE_SUBDOMAIN", "tenant"),
+			("SHAREFILE_USERNAME", "user@mail.com"),
+			("SHAREFILE_PASSWORD", "secret"),
+			("SHAREFILE_CLIENT_ID", "client-id"),
+			("SHAREFILE_CLIENT_SECRET", "client-secret"),
+			("SHAREFILE_READ_TIMEOUT", "30"),
+		];
+		for &(name, value) in vars.iter() {
+			env::set_var(name, value);
+		}
+
+		let builder = Connection::from_env().unwrap();
+		assert_eq!(builder, Connection::new()
+			.subdomain("tenant")
+			.username("user@mail.com")
+			.password("secret")
+			.client_id("client-id")
+			.client_secret("client-secret")
+			.read_timeout(Duration::from_secs(30)));
+
+		env::set_var("SHAREFILE_WRITE_TIMEOUT", "soon");
+		let err = Connection::from_env().unwrap_err();
+		assert_eq!(err.to_string(), "SHAREFILE_WRITE_TIMEOUT environment variable must be the number of seconds: invalid digit found in string");
+		match err {
+			Error::Context(_, ref cause) => match **cause {
+				Error::ParseInt(_) => (),
+				ref other => panic!("Unexpected {:?}", other)
+			},
+			other => panic!("Unexpected {:?}", other)
+		}
+		env::remove_var("SHAREFILE_WRITE_TIMEOUT");
+
+		env::remove_var("SHAREFILE_PASSWORD");
+		let err = Connection::from_env().unwrap_err();
+		assert_eq!(err.to_string(), "SHAREFILE_PASSWORD environment variable is required");
+
+		for &(name, _) in vars.iter() {
+			env::remove_var(name);
+		}
+	}
+
+	#[test]
+	fn authorize_url() {
+		let url = Connection::authorize_url("tenant", "client-id", "https://app.example.com/callback?x=1", "a b&c");
+		assert_eq!(url, "https://tenant.sharefile.com/oauth/authorize?response_type=code&client_id=client-id&redirect_uri=https%3A%2F%2Fapp.example.com%2Fcallback%3Fx%3D1&state=a+b%26c");
+	}
+
+	#[test]
+	fn token_form_grants() {
+		let mut settings = ConnectionSettings::default();
+		settings.client_id = Some("client-id".to_owned());
+		settings.client_secret = Some("client-secret".to_owned());
+		settings.username = Some("user@mail.com".to_owned());
+		settings.password = Some("secret".to_owned());
+
+		let form = Connection::configured(settings.clone()).token_form().unwrap();
+		assert_eq!(form, "grant_type=password&client_id=client-id&client_secret=client-secret&username=user%40mail.com&password=secret");
+
+		settings.authorization_code = Some(("the-code".to_owned(), "https://app.example.com/callback".to_owned()));
+		assert!(Connection::configured(settings.clone()).token_form().is_err());
+
+		settings.username = None;
+		settings.password = None;
+		let form = Connection::configured(settings).token_form().unwrap();
+		assert_eq!(form, "grant_type=authorization_code&code=the-code&redirect_uri=https%3A%2F%2Fapp.example.com%2Fcallback&client_id=client-id&client_secret=client-secret");
+	}
+
+	#[test]
+	fn query_full() {
+		let server = MockServer::start(vec![
+			MockResponse::new(201, "{\"Id\":\"fi-1\"}")
+				.header("ETag", "\"v1\"")
+				.header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT")]);
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+
+		let (status, headers, data) = conn.query_full(Method::Get, "Items(fi-1)".to_string(), None, None).unwrap();
+		assert_eq!(status, StatusCode::Created);
+		assert_eq!(headers.get_raw("ETag"), Some(&[b"\"v1\"".to_vec()][..]));
+		assert_eq!(headers.get_raw("Last-Modified"), Some(&[b"Wed, 21 Oct 2015 07:28:00 GMT".to_vec()][..]));
+		assert_eq!(data, "{\"Id\":\"fi-1\"}");
+	}
+
+	#[test]
+	fn query_json_stream_large() {
+		let values: Vec<String> = (0..20000)
+			.map(|i| format!("{{\"Id\":\"fi-{}\",\"Name\":\"file-{}.txt\"}}", i, i))
+			.collect();
+		let body = format!("{{\"odata.count\":{},\"value\":[{}]}}", values.len(), values.join(","));
+
+		let server = MockServer::start(vec![
+			MockResponse::new(200, body)]);
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+
+		let data = conn.query_json_stream(Method::Get, "Items(fo-1)/Children".to_string(), None, None).unwrap();
+		let list = data.find("value").and_then(|v| v.as_array()).unwrap();
+		assert_eq!(list.len(), 20000);
+		assert_eq!(list[19999].find("Id").and_then(|v| v.as_str()), Some("fi-19999"));
+	}
+
+	#[test]
+	fn query_json_stream_failed() {
+		let server = MockServer::start(vec![
+			MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}")]);
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+
+		let err = conn.query_json_stream(Method::Get, "Items(fo-1)".to_string(), None, None).unwrap_err();
+		assert!(err.is_not_found());
+		assert_eq!(err.status(), Some(StatusCode::NotFound));
+	}
+
+	#[test]
+	fn connections_share_client() {
+		use std::sync::{Arc, RwLock};
+		use hyper::client::Client;
+
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "first"),
+			MockResponse::new(200, "second")]);
+
+		let client = Arc::new(Client::new());
+		let mut first = Connection::with_client(client.clone(), ConnectionSettings::default());
+		let mut second = Connection::with_client(client.clone(), ConnectionSettings::default());
+		assert_eq!(Arc::strong_count(&client), 3);
+
+		for conn in vec![&mut first, &mut second] {
+			conn.auth = Some(Arc::new(RwLock::new(SerializableAuth {
+				subdomain: "tenant".to_string(),
+				token_type: "bearer".to_string(),
+				access_token: "token".to_string(),
+				refresh_token: "".to_string(),
+			})));
+			conn.endpoint = server.url();
+		}
+
+		assert_eq!(first.query_string(Method::Get, "Items".to_string(), None, None).unwrap(), "first");
+		assert_eq!(second.query_string(Method::Get, "Items".to_string(), None, None).unwrap(), "second");
+	}
+
+	#[test]
+	fn connect_timeout_is_applied() {
+		use std::time::Instant;
+
+		let mut settings = ConnectionSettings::default();
+		settings.connect_timeout = Some(Duration::from_millis(200));
+
+		// The address is not routable so the connection is never established
+		let conn = Connection::authenticated(settings, "tenant", "token", "http://10.255.255.1/");
+		let started = Instant::now();
+
+		match conn.query_string(Method::Get, "Items".to_string(), None, None) {
+			Err(Error::Network(_)) => (),
+			other => panic!("Unexpected {:?}", other.map(|_| ()))
+		}
+		assert!(started.elapsed() < Duration::from_secs(5));
+	}
+
+	#[test]
+	fn read_timeout_is_applied() {
+		// The server accepts the connection but never answers
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			let _stream = listener.accept();
+			thread::sleep(Duration::from_secs(5));
+		});
+
+		let mut settings = ConnectionSettings::default();
+		settings.read_timeout = Some(Duration::from_millis(1));
+		settings.write_timeout = Some(Duration::from_millis(1));
+
+		let conn = Connection::configured(settings);
+		match conn.custom_request(Method::Get, format!("http://{}/", addr), None, None) {
+			Err(Error::Network(_)) => (),
+			Err(err) => panic!("Expected network error but got {:?}", err),
+			Ok(_) => panic!("Request must time out"),
+		};
+	}
+
+	#[test]
+	fn refresh_not_authenticated() {
+		let mut conn = Connection::configured(ConnectionSettings::default());
+		match conn.refresh() {
+			Err(Error::Io(ref err)) => assert_eq!(err.kind(), IoErrorKind::PermissionDenied),
+			other => panic!("Expected not authenticated error but got {:?}", other),
+		};
+	}
+
+	#[test]
+	fn query_refreshes_expired_token() {
+		let server = MockServer::start(vec![
+			MockResponse::new(401, ""),
+			MockResponse::new(200, "{\"access_token\":\"access-2\",\"refresh_token\":\"refresh-2\",\"token_type\":\"bearer\",\"subdomain\":\"tenant\"}"),
+			MockResponse::new(200, "first"),
+			MockResponse::new(200, "second")]);
+
+		let mut settings = ConnectionSettings::default();
+		settings.client_id = Some("id".to_owned());
+		settings.client_secret = Some("secret".to_owned());
+		settings.endpoint = Some(server.url());
+		settings.auth_endpoint = Some(server.url());
+
+		let auth = SerializableAuth {
+			subdomain: "tenant".to_owned(),
+			token_type: "bearer".to_owned(),
+			access_token: "access-1".to_owned(),
+			refresh_token: "refresh-1".to_owned(),
+		};
+
+		let conn = Connection::from_auth(settings, auth);
+		let clone = conn.clone();
+		assert_eq!(conn.query_string(Method::Get, "Items(home)".to_owned(), None, None).unwrap(), "first");
+		assert_eq!(clone.query_string(Method::Get, "Items(home)".to_owned(), None, None).unwrap(), "second");
+
+		let requests = server.requests();
+		assert_eq!(requests.len(), 4);
+		assert_eq!(requests[0].header("Authorization"), Some("Bearer access-1"));
+		assert_eq!(requests[1].uri, "/oauth/token");
+		assert!(requests[1].body_string().contains("refresh_token=refresh-1"));
+		assert_eq!(requests[2].header("Authorization"), Some("Bearer access-2"));
+		assert_eq!(requests[3].header("Authorization"), Some("Bearer access-2"));
+
+		let auth = clone.export_auth().unwrap();
+		assert_eq!(auth.access_token, "access-2");
+		assert_eq!(auth.refresh_token, "refresh-2");
+	}
+
+	#[test]
+	fn logout_clears_auth() {
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "")]);
+
+		let mut conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+		conn.logout().unwrap();
+		assert_eq!(conn.access_token(), None);
+		assert_eq!(conn.export_auth(), None);
+
+		let requests = server.requests();
+		assert_eq!(requests[0].uri, "/Sessions/Logout");
+		assert_eq!(requests[0].header("Authorization"), Some("Bearer token"));
+
+		match conn.query(Method::Get, "Items(home)".to_owned(), None, None) {
+			Err(Error::Io(ref err)) => assert_eq!(err.kind(), IoErrorKind::PermissionDenied),
+			other => panic!("Expected not authenticated error but got {:?}", other.map(|r| r.status)),
+		};
+	}
+
+	#[test]
+	fn api_json_with_parameters() {
+		use ::odata::Parameters;
+
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "{\"odata.count\":0,\"value\":[]}"),
+			MockResponse::new(200, "{\"odata.count\":0,\"value\":[]}"),
+			MockResponse::new(200, "{\"Id\":\"zone-1\"}")]);
+
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+		conn.api_json(Method::Get, "Zones", Some(Parameters::new().top(5u32).select(vec!["Id", "Name"])), None).unwrap();
+		conn.api_json(Method::Get, "Zones?includeDisabled=true", Some(Parameters::new().top(5u32)), None).unwrap();
+		let value = conn.api_json(Method::Get, "Zones(zone-1)", None, None).unwrap();
+		assert_eq!(value.find("Id").and_then(|v| v.as_str()), Some("zone-1"));
+
+		let requests = server.requests();
+		assert_eq!(requests[0].uri, "/Zones?%24select=Id%2CName&%24top=5");
+		assert_eq!(requests[1].uri, "/Zones?includeDisabled=true&%24top=5");
+		assert_eq!(requests[2].uri, "/Zones(zone-1)");
+	}
+
+	#[test]
+	fn auth_details_not_connected() {
+		let conn = Connection::configured(ConnectionSettings::default());
+		assert_eq!(conn.subdomain(), None);
+		assert_eq!(conn.access_token(), None);
+		assert_eq!(conn.endpoint(), "");
+	}
+
+	#[test]
+	fn auth_details_connected() {
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", "https://tenant.sf-api.com/sf/v3/");
+		assert_eq!(conn.subdomain(), Some("tenant".to_owned()));
+		assert_eq!(conn.access_token(), Some("token".to_owned()));
+		assert_eq!(conn.endpoint(), "https://tenant.sf-api.com/sf/v3/");
+	}
+
+	#[test]
+	fn restore_exported_auth() {
+		let auth = SerializableAuth {
+			subdomain: "tenant".to_owned(),
+			token_type: "bearer".to_owned(),
+			access_token: "access".to_owned(),
+			refresh_token: "refresh".to_owned(),
+		};
+
+		let conn = Connection::from_auth(ConnectionSettings::default(), auth.clone());
+		assert_eq!(conn.endpoint(), "https://tenant.sf-api.com/sf/v3/");
+		assert_eq!(conn.export_auth(), Some(auth));
+	}
+
+	#[test]
+	fn query_custom_endpoint() {
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "ok")]);
+
+		let auth = SerializableAuth {
+			subdomain: "tenant".to_owned(),
+			token_type: "bearer".to_owned(),
+			access_token: "access".to_owned(),
+			refresh_token: "refresh".to_owned(),
+		};
+
+		let mut settings = ConnectionSettings::default();
+		settings.endpoint = Some(format!("{}sf/v3", server.url()));
+
+		let conn = Connection::from_auth(settings, auth);
+		assert_eq!(conn.endpoint(), format!("{}sf/v3/", server.url()));
+		assert_eq!(conn.query_string(Method::Get, "Items(home)".to_owned(), None, None).unwrap(), "ok");
+		assert_eq!(server.requests()[0].uri, "/sf/v3/Items(home)");
+	}
+
+	#[test]
+	fn proxy_is_configured() {
+		let mut settings = ConnectionSettings::default();
+		settings.proxy = Some(("proxy.example.com".to_owned(), 3128));
+
+		let conn = Connection::configured(settings);
+		assert!(format!("{:?}", conn.client).contains("proxy.example.com"));
+
+		let conn = Connection::configured(ConnectionSettings::default());
+		assert!(format!("{:?}", conn.client).contains("proxy: None"));
+	}
+
+	#[test]
+	fn accept_invalid_certs_connection() {
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "{\"Id\":\"fi-1\"}")]);
+
+		let mut settings = ConnectionSettings::default();
+		settings.danger_accept_invalid_certs = true;
+
+		let conn = Connection::authenticated(settings, "tenant", "token", &server.url());
+		assert!(conn.settings.danger_accept_invalid_certs);
+		assert_eq!(conn.query_string(Method::Get, "Items(fi-1)".to_owned(), None, None).unwrap(), "{\"Id\":\"fi-1\"}");
+	}
+
+	#[test]
+	fn request_through_proxy() {
+		use url::Url;
+
+		// The mock server plays the proxy
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "ok")]);
+		let port = Url::parse(&server.url()).unwrap().port().unwrap();
+
+		let mut settings = ConnectionSettings::default();
+		settings.proxy = Some(("127.0.0.1".to_owned(), port));
+		settings.proxy_auth = Some("Basic dXNlcjpwYXNz".to_owned());
+
+		let conn = Connection::configured(settings);
+		conn.custom_request(Method::Get, "http://storage.example.com/download".to_owned(), None, None).unwrap();
+
+		let requests = server.requests();
+		assert_eq!(requests[0].uri, "http://storage.example.com/download");
+		assert_eq!(requests[0].header("Proxy-Authorization"), Some("Basic dXNlcjpwYXNz"));
+	}
+
+	#[test]
+	fn user_agent_is_sent() {
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "ok"),
+			MockResponse::new(200, "ok")]);
+
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+		conn.query_string(Method::Get, "Items(home)".to_owned(), None, None).unwrap();
+
+		let mut settings = ConnectionSettings::default();
+		settings.user_agent = Some("sync-tool/1.2".to_owned());
+
+		let conn = Connection::configured(settings);
+		conn.custom_request(Method::Get, server.url(), None, None).unwrap();
+
+		let requests = server.requests();
+		assert_eq!(requests[0].header("User-Agent"), Some(format!("shapir/{}", env!("CARGO_PKG_VERSION")).as_str()));
+		assert_eq!(requests[1].header("User-Agent"), Some("sync-tool/1.2"));
+	}
+
+	#[test]
+	fn query_gzip_response() {
+		use std::io::Write;
+		use flate2::Compression;
+		use flate2::write::GzEncoder;
+
+		let mut encoder = GzEncoder::new(Vec::new(), Compression::Default);
+		encoder.write_all(b"{\"Id\":\"fo-home\",\"Name\":\"Home\"}").unwrap();
+		let body = encoder.finish().unwrap();
+
+		let server = MockServer::start(vec![
+			MockResponse::bytes(200, body).header("Content-Encoding", "gzip")]);
+
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+		let value = conn.query_json(Method::Get, "Items(home)".to_owned(), None, None).unwrap();
+		assert_eq!(value.find("Id").and_then(|v| v.as_str()), Some("fo-home"));
+		assert_eq!(server.requests()[0].header("Accept-Encoding"), Some("gzip, deflate"));
+	}
+
+	#[test]
+	fn token_response_not_json() {
+		let body = "<html><head><title>502 Bad Gateway</title></head><body>Bad Gateway</body></html>";
+		match Connection::parse_token_response(body.as_bytes()) {
+			Err(Error::Json(_)) => (),
+			other => panic!("Expected JSON error but got {:?}", other),
+		};
+	}
+
+	#[test]
+	fn token_response_json() {
+		let body = "{\"access_token\":\"access\",\"refresh_token\":\"refresh\",\"token_type\":\"bearer\",\"subdomain\":\"tenant\"}";
+		let auth = Connection::parse_token_response(body.as_bytes()).unwrap();
+		assert_eq!(auth.access_token, "access");
+	}
+
+	#[test]
+	fn retry_idempotent_request() {
+		let server = MockServer::start(vec![
+			MockResponse::new(503, "busy"),
+			MockResponse::new(503, "busy"),
+			MockResponse::new(200, "ok")]);
+
+		let conn = Connection::authenticated(retry_settings(2, Duration::from_millis(1)), "tenant", "token", &server.url());
+		let body = conn.query_string(Method::Get, "Items(home)".to_owned(), None, None).unwrap();
+		assert_eq!(body, "ok");
+		assert_eq!(server.requests().len(), 3);
+	}
+
+	#[test]
+	fn retry_limited_by_max_retries() {
+		let server = MockServer::start(vec![
+			MockResponse::new(503, "busy"),
+			MockResponse::new(503, "busy"),
+			MockResponse::new(200, "ok")]);
+
+		let conn = Connection::authenticated(retry_settings(1, Duration::from_millis(1)), "tenant", "token", &server.url());
+		let response = conn.query(Method::Delete, "Items(id)".to_owned(), None, None).unwrap();
+		assert_eq!(response.status, StatusCode::ServiceUnavailable);
+		assert_eq!(server.requests().len(), 2);
+	}
+
+	#[test]
+	fn retry_not_for_post() {
+		let server = MockServer::start(vec![
+			MockResponse::new(503, "busy"),
+			MockResponse::new(200, "ok")]);
+
+		let conn = Connection::authenticated(retry_settings(2, Duration::from_millis(1)), "tenant", "token", &server.url());
+		let response = conn.query(Method::Post, "Items(home)/Folder".to_owned(), None, Some("{}".to_owned())).unwrap();
+		assert_eq!(response.status, StatusCode::ServiceUnavailable);
+		assert_eq!(server.requests().len(), 1);
+	}
+
+	#[test]
+	fn retry_honors_retry_after() {
+		let server = MockServer::start(vec![
+			MockResponse::new(429, "slow down").header("Retry-After", "0"),
+			MockResponse::new(200, "ok")]);
+
+		// The backoff is long enough to fail the test if the header is ignored
+		let conn = Connection::authenticated(retry_settings(1, Duration::from_secs(60)), "tenant", "token", &server.url());
+		let body = conn.query_string(Method::Get, "Items(home)".to_owned(), None, None).unwrap();
+		assert_eq!(body, "ok");
+		assert_eq!(server.requests().len(), 2);
+	}
+
+	#[test]
+	fn query_with_rate_limit() {
+		use super::super::RateLimit;
+
+		let server = MockServer::start(vec![
+			MockResponse::new(200, "ok").header("X-RateLimit-Remaining", "42")]);
+
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+		let (body, headers) = conn.query_with_headers(Method::Get, "Items(home)".to_owned(), None, None).unwrap();
+		assert_eq!(body, "ok");
+		assert_eq!(RateLimit::from_headers(&headers).remaining, Some(42));
+	}
+
+	#[test]
+	fn query_failed_with_status() {
+		let server = MockServer::start(vec![
+			MockResponse::new(403, "{\"code\":\"Forbidden\",\"message\":{\"lang\":\"en-US\",\"value\":\"Access denied\"},\"reason\":\"Forbidden\"}"),
+			MockResponse::new(404, "{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}"),
+			MockResponse::new(503, "<html>Service Unavailable</html>")]);
+
+		let conn = Connection::authenticated(ConnectionSettings::default(), "tenant", "token", &server.url());
+
+		let err = conn.query_string(Method::Get, "Items(a)".to_owned(), None, None).unwrap_err();
+		assert_eq!(err.status(), Some(StatusCode::Forbidden));
+		assert_eq!(err.service_code(), Some("Forbidden"));
+
+		let err = conn.query_json(Method::Get, "Items(b)".to_owned(), None, None).unwrap_err();
+		assert_eq!(err.status(), Some(StatusCode::NotFound));
+		assert!(err.is_not_found());
+
+		let err = conn.query_string(Method::Get, "Items(c)".to_owned(), None, None).unwrap_err();
+		assert_eq!(err.status(), Some(StatusCode::ServiceUnavailable));
+		assert_eq!(err.service_code(), None);
+	}
 }
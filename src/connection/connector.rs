@@ -0,0 +1,66 @@
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use hyper;
+use hyper::net::{NetworkConnector, HttpStream};
+
+
+/// The connector producing `HttpStream`s which gives up establishing TCP connection
+/// after the timeout given. Each address the host resolves to is tried in turn.
+pub struct TimeoutConnector {
+	timeout: Duration,
+}
+
+
+impl TimeoutConnector {
+	/// Create the connector with the connect `timeout` given
+	pub fn new(timeout: Duration) -> Self {
+		TimeoutConnector {
+			timeout: timeout,
+		}
+	}
+}
+
+
+impl NetworkConnector for TimeoutConnector {
+	type Stream = HttpStream;
+
+	fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<HttpStream> {
+		if scheme != "http" {
+			return Err(hyper::Error::from(io::Error::new(io::ErrorKind::InvalidInput, "Invalid scheme for Http")));
+		}
+
+		let addrs = match (host, port).to_socket_addrs() {
+			Ok(addrs) => addrs,
+			Err(err) => return Err(hyper::Error::from(err))
+		};
+
+		let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "The host is not resolved to any address");
+		for addr in addrs {
+			match TcpStream::connect_timeout(&addr, self.timeout) {
+				Ok(stream) => return Ok(HttpStream(stream)),
+				Err(err) => last_error = err
+			}
+		}
+
+		Err(hyper::Error::from(last_error))
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::net::TcpListener;
+	use std::time::Duration;
+	use hyper::net::NetworkConnector;
+	use super::TimeoutConnector;
+
+	#[test]
+	fn connect_reachable() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let port = listener.local_addr().unwrap().port();
+
+		let connector = TimeoutConnector::new(Duration::from_secs(1));
+		assert!(connector.connect("127.0.0.1", port, "http").is_ok());
+	}
+}
@@ -1,4 +1,8 @@
-use hyper::header::{Headers, ContentType};
+use std::io::{self, Read};
+use std::str;
+use std::time::Duration;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use hyper::header::{Headers, ContentType, ContentEncoding, Encoding};
 use hyper::mime::{Mime, TopLevel, SubLevel};
 
 
@@ -14,4 +18,86 @@ impl ConnectionHelper {
 		headers
 	}
 
+	/// Helper method for reading the delay in seconds from `Retry-After` header.
+	pub fn retry_after(headers: &Headers) -> Option<Duration> {
+		headers.get_raw("Retry-After")
+			.and_then(|values| values.first())
+			.and_then(|value| str::from_utf8(value).ok())
+			.and_then(|value| value.trim().parse::<u64>().ok())
+			.map(|secs| Duration::from_secs(secs))
+	}
+
+	/// Helper method for reading the response `body` into the string decompressing it
+	/// according to `Content-Encoding` header. Supported encodings are `gzip` and `deflate`,
+	/// the body is read as it is for others.
+	pub fn read_body<R: Read>(headers: &Headers, body: R) -> io::Result<String> {
+		let mut data = String::new();
+		ConnectionHelper::body_reader(headers, body)
+			.and_then(|mut reader| reader.read_to_string(&mut data))
+			.map(|_| data)
+	}
+
+	/// Helper method for wrapping the response `body` into the reader decompressing it
+	/// according to `Content-Encoding` header like `read_body()` does.
+	pub fn body_reader<'a, R: Read + 'a>(headers: &Headers, body: R) -> io::Result<Box<Read + 'a>> {
+		let encoding = headers.get::<ContentEncoding>()
+			.and_then(|&ContentEncoding(ref encodings)| encodings.last().cloned());
+
+		match encoding {
+			Some(Encoding::Gzip) => GzDecoder::new(body)
+				.map(|decoder| Box::new(decoder) as Box<Read>),
+			Some(Encoding::Deflate) => Ok(Box::new(ZlibDecoder::new(body))),
+			_ => Ok(Box::new(body))
+		}
+	}
+
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use hyper::header::Headers;
+	use super::ConnectionHelper;
+
+	#[test]
+	fn retry_after_seconds() {
+		let mut headers = Headers::new();
+		headers.set_raw("Retry-After", vec![b"120".to_vec()]);
+		assert_eq!(ConnectionHelper::retry_after(&headers), Some(Duration::from_secs(120)));
+	}
+
+	#[test]
+	fn retry_after_missing() {
+		let headers = Headers::new();
+		assert_eq!(ConnectionHelper::retry_after(&headers), None);
+	}
+
+	#[test]
+	fn retry_after_http_date() {
+		let mut headers = Headers::new();
+		headers.set_raw("Retry-After", vec![b"Wed, 21 Oct 2015 07:28:00 GMT".to_vec()]);
+		assert_eq!(ConnectionHelper::retry_after(&headers), None);
+	}
+
+	#[test]
+	fn read_body_identity() {
+		let headers = Headers::new();
+		assert_eq!(ConnectionHelper::read_body(&headers, &b"plain"[..]).unwrap(), "plain");
+	}
+
+	#[test]
+	fn read_body_deflate() {
+		use std::io::Write;
+		use flate2::Compression;
+		use flate2::write::ZlibEncoder;
+
+		let mut encoder = ZlibEncoder::new(Vec::new(), Compression::Default);
+		encoder.write_all(b"deflated").unwrap();
+		let body = encoder.finish().unwrap();
+
+		let mut headers = Headers::new();
+		headers.set_raw("Content-Encoding", vec![b"deflate".to_vec()]);
+		assert_eq!(ConnectionHelper::read_body(&headers, &body[..]).unwrap(), "deflated");
+	}
 }
@@ -5,8 +5,17 @@ mod builder;
 mod settings;
 mod url;
 mod helper;
+mod auth;
+mod rate_limit;
+mod connector;
+#[cfg(feature = "futures")]
+mod async_connection;
 
 pub use self::connection::Connection;
 pub use self::settings::ConnectionSettings;
 pub use self::builder::ConnectionBuilder;
 pub use self::helper::ConnectionHelper;
+pub use self::auth::SerializableAuth;
+pub use self::rate_limit::RateLimit;
+#[cfg(feature = "futures")]
+pub use self::async_connection::{AsyncConnection, AsyncResult};
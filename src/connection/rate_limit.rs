@@ -0,0 +1,85 @@
+use std::str;
+use std::time::Duration;
+use hyper::header::Headers;
+use super::ConnectionHelper;
+
+
+/// Rate limiting information returned in API response headers
+///
+/// Each field is `None` if the response does not contain the corresponding header.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RateLimit {
+	/// Maximum number of requests allowed in the current window (`X-RateLimit-Limit`)
+	pub limit: Option<u64>,
+	/// Number of requests remaining in the current window (`X-RateLimit-Remaining`)
+	pub remaining: Option<u64>,
+	/// Time when the current window resets as reported by the API (`X-RateLimit-Reset`)
+	pub reset: Option<u64>,
+	/// Delay requested by the API before making the next request (`Retry-After`)
+	pub retry_after: Option<Duration>,
+}
+
+
+impl RateLimit {
+	/// Parse rate limiting information from response headers.
+	pub fn from_headers(headers: &Headers) -> RateLimit {
+		RateLimit {
+			limit: RateLimit::number(headers, "X-RateLimit-Limit"),
+			remaining: RateLimit::number(headers, "X-RateLimit-Remaining"),
+			reset: RateLimit::number(headers, "X-RateLimit-Reset"),
+			retry_after: ConnectionHelper::retry_after(headers),
+		}
+	}
+
+	// Read the header value as a number
+	fn number(headers: &Headers, name: &str) -> Option<u64> {
+		headers.get_raw(name)
+			.and_then(|values| values.first())
+			.and_then(|value| str::from_utf8(value).ok())
+			.and_then(|value| value.trim().parse::<u64>().ok())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use hyper::header::Headers;
+	use super::RateLimit;
+
+	#[test]
+	fn rate_limit_all_headers() {
+		let mut headers = Headers::new();
+		headers.set_raw("X-RateLimit-Limit", vec![b"1000".to_vec()]);
+		headers.set_raw("X-RateLimit-Remaining", vec![b"998".to_vec()]);
+		headers.set_raw("X-RateLimit-Reset", vec![b"1539000000".to_vec()]);
+		headers.set_raw("Retry-After", vec![b"30".to_vec()]);
+
+		let rate_limit = RateLimit::from_headers(&headers);
+		assert_eq!(rate_limit.limit, Some(1000));
+		assert_eq!(rate_limit.remaining, Some(998));
+		assert_eq!(rate_limit.reset, Some(1539000000));
+		assert_eq!(rate_limit.retry_after, Some(Duration::from_secs(30)));
+	}
+
+	#[test]
+	fn rate_limit_missing_headers() {
+		let mut headers = Headers::new();
+		headers.set_raw("X-RateLimit-Remaining", vec![b"5".to_vec()]);
+
+		let rate_limit = RateLimit::from_headers(&headers);
+		assert_eq!(rate_limit.limit, None);
+		assert_eq!(rate_limit.remaining, Some(5));
+		assert_eq!(rate_limit.reset, None);
+		assert_eq!(rate_limit.retry_after, None);
+	}
+
+	#[test]
+	fn rate_limit_invalid_header() {
+		let mut headers = Headers::new();
+		headers.set_raw("X-RateLimit-Limit", vec![b"many".to_vec()]);
+
+		let rate_limit = RateLimit::from_headers(&headers);
+		assert_eq!(rate_limit.limit, None);
+	}
+}
@@ -10,16 +10,41 @@ pub struct ConnectionSettings {
     pub read_timeout: Option<Duration>,
     /// Write Timeout
     pub write_timeout: Option<Duration>,
+	/// Timeout of establishing TCP connection, not applied to connections through the proxy
+	pub connect_timeout: Option<Duration>,
+	/// Maximum number of retries of idempotent requests failed with `429` or `5XX` status
+	pub max_retries: Option<u32>,
+	/// Delay before the first retry, doubled on each next retry up to one minute
+	/// or the delay itself if it's longer
+	pub retry_backoff: Option<Duration>,
 	/// Subdomain (*required*)
 	pub subdomain: Option<String>,
-	/// Username (*required*)
+	/// Username (*required* with `password`)
 	pub username: Option<String>,
-	/// Password (*required*)
+	/// Password (*required* if `authorization_code` is not set)
 	pub password: Option<String>,
+	/// Authorization code and the redirect URI it was issued for, used instead of
+	/// `username` and `password`
+	pub authorization_code: Option<(String, String)>,
 	/// Client ID (*required*)
 	pub client_id: Option<String>,
 	/// Client Secret (*required*)
 	pub client_secret: Option<String>,
+	/// Base URL of the API replacing `https://{subdomain}.sf-api.com/sf/{api_version}/`
+	pub endpoint: Option<String>,
+	/// API version used in the default endpoint, `v3` if not set
+	pub api_version: Option<String>,
+	/// Base URL of OAuth token requests replacing `https://{subdomain}.sharefile.com/`
+	pub auth_endpoint: Option<String>,
+	/// HTTP proxy host and port all requests are sent through
+	pub proxy: Option<(String, u16)>,
+	/// Value of `Proxy-Authorization` header sent with each request, e.g. `Basic dXNlcjpwYXNz`
+	pub proxy_auth: Option<String>,
+	/// Value of `User-Agent` header sent with each request, `shapir/<version>` if not set
+	pub user_agent: Option<String>,
+	/// Accept TLS certificates which cannot be verified, e.g. self-signed ones.
+	/// **Insecure**, see `ConnectionBuilder::danger_accept_invalid_certs()`
+	pub danger_accept_invalid_certs: bool,
 }
 
 
@@ -28,11 +53,74 @@ impl Default for ConnectionSettings {
 		ConnectionSettings {
 			read_timeout: None,
             write_timeout: None,
+			connect_timeout: None,
+			max_retries: None,
+			retry_backoff: None,
 			subdomain: None,
 			username: None,
 			password: None,
+			authorization_code: None,
 			client_id: None,
 			client_secret: None,
+			endpoint: None,
+			api_version: None,
+			auth_endpoint: None,
+			proxy: None,
+			proxy_auth: None,
+			user_agent: None,
+			danger_accept_invalid_certs: false,
 		}
 	}
+}
+
+
+impl ConnectionSettings {
+	/// Get the base URL of the API for the account on `subdomain`.
+	pub fn api_endpoint(&self, subdomain: &str) -> String {
+		match self.endpoint {
+			Some(ref endpoint) if endpoint.ends_with('/') => endpoint.clone(),
+			Some(ref endpoint) => format!("{}/", endpoint),
+			None => format!("https://{}.sf-api.com/sf/{}/", subdomain, self.api_version.as_ref().map_or("v3", |v| v.as_str())),
+		}
+	}
+
+	/// Get the URL of OAuth token endpoint for the account on `subdomain`.
+	pub fn token_endpoint(&self, subdomain: &str) -> String {
+		match self.auth_endpoint {
+			Some(ref endpoint) if endpoint.ends_with('/') => format!("{}oauth/token", endpoint),
+			Some(ref endpoint) => format!("{}/oauth/token", endpoint),
+			None => format!("https://{}.sharefile.com/oauth/token", subdomain),
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::ConnectionSettings;
+
+	#[test]
+	fn default_api_endpoint() {
+		let settings = ConnectionSettings::default();
+		assert_eq!(settings.api_endpoint("tenant"), "https://tenant.sf-api.com/sf/v3/");
+	}
+
+	#[test]
+	fn custom_api_endpoint() {
+		let mut settings = ConnectionSettings::default();
+		settings.api_version = Some("v4".to_owned());
+		assert_eq!(settings.api_endpoint("tenant"), "https://tenant.sf-api.com/sf/v4/");
+
+		settings.endpoint = Some("https://tenant.sf-api.eu/sf/v3".to_owned());
+		assert_eq!(settings.api_endpoint("tenant"), "https://tenant.sf-api.eu/sf/v3/");
+	}
+
+	#[test]
+	fn token_endpoint() {
+		let mut settings = ConnectionSettings::default();
+		assert_eq!(settings.token_endpoint("tenant"), "https://tenant.sharefile.com/oauth/token");
+
+		settings.auth_endpoint = Some("http://127.0.0.1:8080".to_owned());
+		assert_eq!(settings.token_endpoint("tenant"), "http://127.0.0.1:8080/oauth/token");
+	}
 }
\ No newline at end of file
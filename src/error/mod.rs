@@ -7,12 +7,15 @@ mod service_error;
 use std::{self, fmt};
 use std::error::Error as StdError;
 use serde_json::Value;
+use hyper::status::StatusCode;
 
 pub use std::io::Error as IoError;
 pub use std::io::ErrorKind as IoErrorKind;
 pub use hyper::error::Error as NetworkError;
 pub use url::ParseError as UrlError;
 pub use serde_json::error::Error as JsonError;
+pub use chrono::ParseError as DateError;
+pub use std::num::ParseIntError;
 pub use self::service_error::ServiceError;
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -25,7 +28,11 @@ pub enum Error {
     Url(UrlError),
     Json(JsonError),
     Service(ServiceError),
+    Date(DateError),
+    ParseInt(ParseIntError),
+    Ambiguous(Vec<String>),
     Other(String),
+    Context(&'static str, Box<Error>),
 }
 
 
@@ -60,6 +67,58 @@ impl Error {
         Err(Error::from(err.into()))
     }
 
+    /// Get the error code returned by the API if that is the service error.
+    pub fn service_code(&self) -> Option<&str> {
+        match self {
+            &Error::Service(ref err) => err.code().as_ref().map(|code| code.as_str()),
+            &Error::Context(_, ref err) => err.service_code(),
+            _ => None,
+        }
+    }
+
+    /// Get HTTP status code of the failed API response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            &Error::Service(ref err) => err.status(),
+            &Error::Context(_, ref err) => err.status(),
+            _ => None,
+        }
+    }
+
+    /// Get IDs of the items found if the path given is ambiguous and points to more
+    /// than one item.
+    pub fn candidates(&self) -> Option<&[String]> {
+        match self {
+            &Error::Ambiguous(ref ids) => Some(ids),
+            &Error::Context(_, ref err) => err.candidates(),
+            _ => None,
+        }
+    }
+
+    /// Test if the API reported the requested entity is not found.
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            &Error::Service(ref err) => err.is_not_found(),
+            &Error::Context(_, ref err) => err.is_not_found(),
+            _ => false,
+        }
+    }
+
+    /// Test if the API reported the request conflicts with the current state of the entity,
+    /// e.g. the item is checked out by another user.
+    pub fn is_conflict(&self) -> bool {
+        match self {
+            &Error::Service(ref err) => err.is_conflict(),
+            &Error::Context(_, ref err) => err.is_conflict(),
+            _ => false,
+        }
+    }
+
+    /// Wrap the error with the message describing what was going on when the error occured.
+    pub fn context(self, message: &'static str) -> Error {
+        Error::Context(message, Box::new(self))
+    }
+
     // Parse the error from the result JSON
     pub fn from_json(value: Value) -> Result<Value> {
         ServiceError::from_json(&value)
@@ -76,7 +135,11 @@ impl StdError for Error {
             &Error::Url(ref err) => err.description(),
             &Error::Json(ref err) => err.description(),
             &Error::Service(ref err) => err.description(),
+            &Error::Date(ref err) => err.description(),
+            &Error::ParseInt(ref err) => err.description(),
+            &Error::Ambiguous(_) => "There are more than one Item",
             &Error::Other(ref msg) => &msg,
+            &Error::Context(msg, _) => msg,
         }
     }
 
@@ -87,7 +150,26 @@ impl StdError for Error {
             &Error::Url(ref err) => err.cause(),
             &Error::Json(ref err) => err.cause(),
             &Error::Service(ref err) => err.cause(),
+            &Error::Date(ref err) => err.cause(),
+            &Error::ParseInt(ref err) => err.cause(),
+            &Error::Ambiguous(_) => None,
             &Error::Other(_) => None,
+            &Error::Context(_, ref err) => Some(err.as_ref()),
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            &Error::Io(ref err) => Some(err),
+            &Error::Network(ref err) => Some(err),
+            &Error::Url(ref err) => Some(err),
+            &Error::Json(ref err) => Some(err),
+            &Error::Service(ref err) => Some(err),
+            &Error::Date(ref err) => Some(err),
+            &Error::ParseInt(ref err) => Some(err),
+            &Error::Ambiguous(_) => None,
+            &Error::Other(_) => None,
+            &Error::Context(_, ref err) => Some(err.as_ref()),
         }
     }
 }
@@ -95,7 +177,13 @@ impl StdError for Error {
 
 impl From<IoError> for Error {
     fn from(err: IoError) -> Error {
-        Error::Io(err)
+        // IO errors produced by the library itself, e.g. in `Content` writes, wrap `Error`
+        if err.get_ref().map_or(false, |inner| inner.is::<Error>()) {
+            *err.into_inner().unwrap().downcast::<Error>().unwrap()
+        }
+        else {
+            Error::Io(err)
+        }
     }
 }
 
@@ -128,6 +216,20 @@ impl From<ServiceError> for Error {
 }
 
 
+impl From<DateError> for Error {
+    fn from(err: DateError) -> Error {
+        Error::Date(err)
+    }
+}
+
+
+impl From<ParseIntError> for Error {
+    fn from(err: ParseIntError) -> Error {
+        Error::ParseInt(err)
+    }
+}
+
+
 impl From<String> for Error {
     fn from(err: String) -> Error {
         Error::Other(err)
@@ -150,7 +252,11 @@ impl fmt::Display for Error {
             &Error::Url(ref err) => fmt::Display::fmt(err, f),
             &Error::Json(ref err) => fmt::Display::fmt(err, f),
             &Error::Service(ref err) => fmt::Display::fmt(err, f),
+            &Error::Date(ref err) => fmt::Display::fmt(err, f),
+            &Error::ParseInt(ref err) => fmt::Display::fmt(err, f),
+            &Error::Ambiguous(ref ids) => write!(f, "There are more than one Item: {}", ids.join(", ")),
             &Error::Other(ref msg) => write!(f, "{}", msg),
+            &Error::Context(msg, ref err) => write!(f, "{}: {}", msg, err),
         }
     }
 }
@@ -161,6 +267,13 @@ mod tests {
     use super::Error;
     use serde_json::{self, Value};
 
+    #[test]
+    fn ambiguous_candidates() {
+        let err = Error::Ambiguous(vec!["fi-1".to_owned(), "fi-2".to_owned()]).context("Cannot resolve parent ID");
+        assert_eq!(err.candidates(), Some(&["fi-1".to_owned(), "fi-2".to_owned()][..]));
+        assert_eq!(Error::from("Failed").candidates(), None);
+    }
+
     #[test]
     fn parse_error_json() {
         let data: Value = serde_json::from_str("{\"code\":\"BadRequest\",\"message\":{\"lang\":\"en-US\",\"value\":\"Invalid Argument Items.Folder\"},\"reason\":\"BadRequest\"}").unwrap();
@@ -168,6 +281,64 @@ mod tests {
         assert!(error.is_err());
     }
 
+    #[test]
+    fn parse_not_found_json() {
+        let data: Value = serde_json::from_str("{\"code\":\"NotFound\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item not found\"},\"reason\":\"NotFound\"}").unwrap();
+        let error = Error::from_json(data).unwrap_err();
+        assert!(error.is_not_found());
+        assert_eq!(error.service_code(), Some("NotFound"));
+    }
+
+    #[test]
+    fn parse_bad_request_json() {
+        let data: Value = serde_json::from_str("{\"code\":\"BadRequest\",\"message\":{\"lang\":\"en-US\",\"value\":\"Invalid Argument Items.Folder\"},\"reason\":\"BadRequest\"}").unwrap();
+        let error = Error::from_json(data).unwrap_err();
+        assert!(!error.is_not_found());
+        assert_eq!(error.service_code(), Some("BadRequest"));
+    }
+
+    #[test]
+    fn parse_conflict_json() {
+        let data: Value = serde_json::from_str("{\"code\":\"Conflict\",\"message\":{\"lang\":\"en-US\",\"value\":\"Item is checked out\"},\"reason\":\"Conflict\"}").unwrap();
+        let error = Error::from_json(data).unwrap_err();
+        assert!(error.is_conflict());
+        assert!(!error.is_not_found());
+    }
+
+    #[test]
+    fn parse_gateway_error_json() {
+        let data: Value = serde_json::from_str("{\"code\":400,\"message\":\"bad\"}").unwrap();
+        let error = Error::from_json(data).unwrap_err();
+        assert_eq!(error.service_code(), None);
+        assert_eq!(error.to_string(), "bad");
+
+        // Not an error without the message
+        let data: Value = serde_json::from_str("{\"code\":\"InternalError\",\"message\":42}").unwrap();
+        assert!(Error::from_json(data).is_ok());
+    }
+
+    #[test]
+    fn error_with_context() {
+        use std::error::Error as StdError;
+
+        let error = Error::from("Item not found").context("Cannot resolve parent ID");
+        assert_eq!(error.to_string(), "Cannot resolve parent ID: Item not found");
+        assert_eq!(error.description(), "Cannot resolve parent ID");
+        assert_eq!(error.cause().map(|e| e.to_string()), Some("Item not found".to_owned()));
+    }
+
+    #[test]
+    fn error_source() {
+        use std::error::Error as StdError;
+        use super::NetworkError;
+
+        let error = Error::from(NetworkError::Status);
+        assert_eq!(error.source().map(|e| e.to_string()), Some(NetworkError::Status.to_string()));
+
+        let error = Error::from("Item not found");
+        assert!(error.source().is_none());
+    }
+
     #[test]
     fn parse_success_json() {
         let data: Value = serde_json::from_str("{\"Id\":\"some-file-id\"}").unwrap();
@@ -4,12 +4,14 @@
 use std::fmt;
 use std::error;
 use serde_json::Value;
+use hyper::status::StatusCode;
 
 
 #[derive(Debug)]
 pub struct ServiceError {
     code: Option<String>,
     message: String,
+    status: Option<StatusCode>,
 }
 
 
@@ -19,28 +21,33 @@ impl ServiceError {
         ServiceError {
             code: code.map(|c| c.into()),
             message: message.into(),
+            status: None,
         }
     }
 
-    /// Try to construct the service error from JSON
+    /// Set HTTP status code of the response the error is returned with
+    pub fn with_status(mut self, status: StatusCode) -> ServiceError {
+        self.status = Some(status);
+        self
+    }
+
+    /// Try to construct the service error from JSON. The error is constructed only if
+    /// the JSON has `code` and `message` properties. The message is either the plain string
+    /// or the object with `value` string, the code is kept only if it's the string.
     pub fn from_json(value: &Value) -> Option<ServiceError> {
         value.find("code")
             .and_then(|code| {
-                // Error code
-                let code = code.as_str()
-                    .unwrap();
-
                 // Error message
                 let message = value.find("message")
-                    .unwrap()
-                    .find("value")
-                    .unwrap()
-                    .as_str()
-                    .unwrap();
-
-                Some(ServiceError {
-                    code: Some(String::from(code)),
+                    .and_then(|message| match message.find("value") {
+                        Some(v) => v.as_str(),
+                        None => message.as_str()
+                    });
+
+                message.map(|message| ServiceError {
+                    code: code.as_str().map(|code| String::from(code)),
                     message: String::from(message),
+                    status: None,
                 })
             })
     }
@@ -50,6 +57,27 @@ impl ServiceError {
         &self.code
     }
 
+    /// Get HTTP status code of the response the error is returned with
+    pub fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// Test if the error code is `NotFound` or the response status is `404 Not Found`
+    pub fn is_not_found(&self) -> bool {
+        match self.code {
+            Some(ref code) => code == "NotFound",
+            None => self.status == Some(StatusCode::NotFound),
+        }
+    }
+
+    /// Test if the error code is `Conflict` or the response status is `409 Conflict`
+    pub fn is_conflict(&self) -> bool {
+        match self.code {
+            Some(ref code) => code == "Conflict",
+            None => self.status == Some(StatusCode::Conflict),
+        }
+    }
+
     /// Get error message
     pub fn message(&self) -> &String {
         &self.message
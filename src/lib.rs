@@ -8,6 +8,7 @@
 //! - [serde_json](https://crates.io/crates/serde_json) as JSON encoding/decoding facility.
 //! - And other great crates.
 
+extern crate serde;
 extern crate serde_json;
 extern crate hyper;
 extern crate url;
@@ -15,6 +16,9 @@ extern crate chrono;
 extern crate md5;
 extern crate rustc_serialize;
 extern crate email;
+extern crate flate2;
+#[cfg(feature = "futures")]
+extern crate futures;
 
 
 pub mod error;
@@ -22,6 +26,11 @@ pub mod connection;
 pub mod odata;
 pub mod api;
 
+#[cfg(test)]
+mod mock;
+
 
 pub use connection::{Connection, ConnectionBuilder, ConnectionSettings};
+#[cfg(feature = "futures")]
+pub use connection::AsyncConnection;
 pub use error::{Error, Result};
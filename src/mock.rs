@@ -0,0 +1,211 @@
+//! Canned HTTP server used in tests
+//!
+//! The server answers accepted connections with the responses given, one response
+//! per connection in the order given, and records the requests received.
+
+#![allow(dead_code)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+
+/// The response the server replies with
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+	status: u16,
+	headers: Vec<(String, String)>,
+	body: Vec<u8>,
+	length: bool,
+}
+
+
+impl MockResponse {
+	/// Create the response with the textual body
+	pub fn new<T: Into<String>>(status: u16, body: T) -> Self {
+		MockResponse::bytes(status, body.into().into_bytes())
+	}
+
+	/// Create the response with the binary body
+	pub fn bytes(status: u16, body: Vec<u8>) -> Self {
+		MockResponse {
+			status: status,
+			headers: Vec::new(),
+			body: body,
+			length: true,
+		}
+	}
+
+	/// Omit `Content-Length` header, the body ends when the connection is closed
+	pub fn without_length(mut self) -> Self {
+		self.length = false;
+		self
+	}
+
+	/// Add the header to the response
+	pub fn header<T: Into<String>>(mut self, name: T, value: T) -> Self {
+		self.headers.push((name.into(), value.into()));
+		self
+	}
+}
+
+
+/// The request the server received
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+	/// Request method
+	pub method: String,
+	/// Request URI
+	pub uri: String,
+	/// Request headers
+	pub headers: Vec<(String, String)>,
+	/// Request body
+	pub body: Vec<u8>,
+}
+
+
+impl MockRequest {
+	/// Get the value of the header, the name is case insensitive
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers.iter()
+			.find(|h| h.0.to_lowercase() == name.to_lowercase())
+			.map(|h| h.1.as_str())
+	}
+
+	/// Get the body as the string
+	pub fn body_string(&self) -> String {
+		String::from_utf8_lossy(&self.body).into_owned()
+	}
+}
+
+
+/// The server running in the background thread
+pub struct MockServer {
+	url: String,
+	requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+
+impl MockServer {
+	/// Start the server which answers with `responses`
+	pub fn start(responses: Vec<MockResponse>) -> MockServer {
+		MockServer::start_with(|_| responses)
+	}
+
+	/// Start the server which answers with responses built knowing the server URL
+	pub fn start_with<F>(build: F) -> MockServer where F: FnOnce(&str) -> Vec<MockResponse> {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/", listener.local_addr().unwrap());
+		let responses = build(&url);
+		let requests = Arc::new(Mutex::new(Vec::new()));
+
+		let recorded = requests.clone();
+		thread::spawn(move || {
+			for response in responses {
+				let mut stream = match listener.accept() {
+					Ok((stream, _)) => stream,
+					Err(_) => return
+				};
+
+				if let Some(request) = read_request(&mut stream) {
+					recorded.lock().unwrap().push(request);
+				}
+
+				write_response(&mut stream, &response);
+			}
+		});
+
+		MockServer {
+			url: url,
+			requests: requests,
+		}
+	}
+
+	/// Get the base URL of the server, e.g. `http://127.0.0.1:1234/`
+	pub fn url(&self) -> String {
+		self.url.clone()
+	}
+
+	/// Get requests received so far
+	pub fn requests(&self) -> Vec<MockRequest> {
+		self.requests.lock().unwrap().clone()
+	}
+}
+
+
+// Read the request head and the body of `Content-Length` bytes
+fn read_request(stream: &mut TcpStream) -> Option<MockRequest> {
+	let mut data: Vec<u8> = Vec::new();
+	let mut buf = [0; 4096];
+
+	// Read until the end of the head
+	let head_len = loop {
+		if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+			break pos + 4;
+		}
+
+		match stream.read(&mut buf) {
+			Ok(0) | Err(_) => return None,
+			Ok(n) => data.extend_from_slice(&buf[0..n]),
+		};
+	};
+
+	let head = String::from_utf8_lossy(&data[0..head_len]).into_owned();
+	let mut lines = head.split("\r\n");
+
+	let mut request_line = lines.next().unwrap_or("").split(' ');
+	let method = request_line.next().unwrap_or("").to_owned();
+	let uri = request_line.next().unwrap_or("").to_owned();
+
+	let headers: Vec<(String, String)> = lines
+		.filter(|line| !line.is_empty())
+		.filter_map(|line| {
+			let mut parts = line.splitn(2, ':');
+			match (parts.next(), parts.next()) {
+				(Some(name), Some(value)) => Some((name.trim().to_owned(), value.trim().to_owned())),
+				_ => None
+			}
+		})
+		.collect();
+
+	// Read the rest of the body
+	let body_len = headers.iter()
+		.find(|h| h.0.to_lowercase() == "content-length")
+		.and_then(|h| h.1.parse::<usize>().ok())
+		.unwrap_or(0);
+
+	while data.len() < head_len + body_len {
+		match stream.read(&mut buf) {
+			Ok(0) | Err(_) => break,
+			Ok(n) => data.extend_from_slice(&buf[0..n]),
+		};
+	}
+
+	Some(MockRequest {
+		method: method,
+		uri: uri,
+		headers: headers,
+		body: data[head_len..].to_vec(),
+	})
+}
+
+
+// Write the response and ask the client to close the connection
+fn write_response(stream: &mut TcpStream, response: &MockResponse) {
+	let mut head = format!("HTTP/1.1 {} Mock\r\nConnection: close\r\n", response.status);
+
+	if response.length {
+		head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+	}
+
+	for &(ref name, ref value) in &response.headers {
+		head.push_str(&format!("{}: {}\r\n", name, value));
+	}
+
+	head.push_str("\r\n");
+
+	let _ = stream.write_all(head.as_bytes());
+	let _ = stream.write_all(&response.body);
+	let _ = stream.flush();
+}
@@ -0,0 +1,197 @@
+/// OData `$filter` expression
+///
+/// The expression converts into the string so it can be passed to `filter()` and
+/// `filter_add()` of `Parameters`. Expressions combined with `and()` and `or()` are
+/// enclosed in parentheses so they keep the meaning when joined with other filters.
+///
+/// ## Example
+///
+/// ```rust
+/// use shapir::odata::{Parameters, Filter};
+///
+///	let opts = Parameters::new()
+///		.filter_add(Filter::eq("Name", "report.pdf").or(Filter::gt("FileSizeBytes", 100)))
+///		.filter_add("IsHidden eq false");
+/// ```
+///
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+	expr: String,
+}
+
+
+/// The literal value of the `Filter` expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterValue(String);
+
+
+impl Filter {
+	/// Create the filter from the raw expression
+	pub fn raw<T>(expr: T) -> Self where T: Into<String> {
+		Filter {
+			expr: expr.into(),
+		}
+	}
+
+	/// Create `field eq value` expression
+	pub fn eq<T>(field: &str, value: T) -> Self where T: Into<FilterValue> {
+		Filter::compare(field, "eq", value.into())
+	}
+
+	/// Create `field ne value` expression
+	pub fn ne<T>(field: &str, value: T) -> Self where T: Into<FilterValue> {
+		Filter::compare(field, "ne", value.into())
+	}
+
+	/// Create `field gt value` expression
+	pub fn gt<T>(field: &str, value: T) -> Self where T: Into<FilterValue> {
+		Filter::compare(field, "gt", value.into())
+	}
+
+	/// Create `field ge value` expression
+	pub fn ge<T>(field: &str, value: T) -> Self where T: Into<FilterValue> {
+		Filter::compare(field, "ge", value.into())
+	}
+
+	/// Create `field lt value` expression
+	pub fn lt<T>(field: &str, value: T) -> Self where T: Into<FilterValue> {
+		Filter::compare(field, "lt", value.into())
+	}
+
+	/// Create `field le value` expression
+	pub fn le<T>(field: &str, value: T) -> Self where T: Into<FilterValue> {
+		Filter::compare(field, "le", value.into())
+	}
+
+	/// Combine expressions with `and`
+	pub fn and(self, other: Filter) -> Self {
+		Filter::raw(format!("({} and {})", self.expr, other.expr))
+	}
+
+	/// Combine expressions with `or`
+	pub fn or(self, other: Filter) -> Self {
+		Filter::raw(format!("({} or {})", self.expr, other.expr))
+	}
+
+	/// Negate the expression
+	pub fn not(self) -> Self {
+		Filter::raw(format!("not ({})", self.expr))
+	}
+
+	fn compare(field: &str, op: &str, value: FilterValue) -> Self {
+		Filter::raw(format!("{} {} {}", field, op, value.0))
+	}
+}
+
+
+impl Into<String> for Filter {
+	fn into(self) -> String {
+		self.expr
+	}
+}
+
+
+impl ToString for Filter {
+	fn to_string(&self) -> String {
+		self.expr.clone()
+	}
+}
+
+
+// String literals are quoted with single quotes doubled inside
+impl<'a> From<&'a str> for FilterValue {
+	fn from(v: &'a str) -> Self {
+		FilterValue(format!("'{}'", v.replace("'", "''")))
+	}
+}
+
+
+impl From<String> for FilterValue {
+	fn from(v: String) -> Self {
+		FilterValue::from(v.as_str())
+	}
+}
+
+
+impl From<bool> for FilterValue {
+	fn from(v: bool) -> Self {
+		FilterValue(v.to_string())
+	}
+}
+
+
+impl From<i32> for FilterValue {
+	fn from(v: i32) -> Self {
+		FilterValue(v.to_string())
+	}
+}
+
+
+impl From<i64> for FilterValue {
+	fn from(v: i64) -> Self {
+		FilterValue(v.to_string())
+	}
+}
+
+
+impl From<u32> for FilterValue {
+	fn from(v: u32) -> Self {
+		FilterValue(v.to_string())
+	}
+}
+
+
+impl From<u64> for FilterValue {
+	fn from(v: u64) -> Self {
+		FilterValue(v.to_string())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::Filter;
+	use super::super::Parameters;
+
+	#[test]
+	fn filter_compare() {
+		assert_eq!(Filter::eq("Name", "x").to_string(), "Name eq 'x'");
+		assert_eq!(Filter::ne("Name", "It's".to_string()).to_string(), "Name ne 'It''s'");
+		assert_eq!(Filter::gt("Size", 100).to_string(), "Size gt 100");
+		assert_eq!(Filter::ge("Size", 100u64).to_string(), "Size ge 100");
+		assert_eq!(Filter::lt("Size", -1i64).to_string(), "Size lt -1");
+		assert_eq!(Filter::le("Size", 5u32).to_string(), "Size le 5");
+		assert_eq!(Filter::eq("IsHidden", false).to_string(), "IsHidden eq false");
+	}
+
+	#[test]
+	fn filter_or() {
+		let filter = Filter::eq("Name", "x").or(Filter::gt("Size", 100));
+		assert_eq!(filter.to_string(), "(Name eq 'x' or Size gt 100)");
+	}
+
+	#[test]
+	fn filter_grouping() {
+		let filter = Filter::eq("A", 1)
+			.or(Filter::eq("B", 2))
+			.and(Filter::eq("C", 3).or(Filter::raw("D eq 4")).not());
+		assert_eq!(filter.to_string(), "((A eq 1 or B eq 2) and not ((C eq 3 or D eq 4)))");
+	}
+
+	#[test]
+	fn filter_in_parameters() {
+		use url::form_urlencoded;
+
+		let opts: String = Parameters::new()
+			.filter_add(Filter::eq("A", 1).or(Filter::eq("B", 2)))
+			.filter_add("C eq 3")
+			.into();
+
+		let should_be = form_urlencoded::Serializer::new(String::new())
+			.append_pair("$filter", "(A eq 1 or B eq 2) and C eq 3")
+			.finish();
+
+		assert_eq!(opts, should_be);
+	}
+}
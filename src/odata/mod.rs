@@ -2,6 +2,15 @@
 
 
 mod parameters;
+mod filter;
+mod order_by;
 
 
 pub use self::parameters::Parameters;
+pub use self::filter::{Filter, FilterValue};
+pub use self::order_by::{OrderBy, Direction};
+
+
+/// The former name of `Parameters`.
+#[deprecated(since = "0.5.0", note = "use `Parameters` instead")]
+pub type QueryOptions = Parameters;
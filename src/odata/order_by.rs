@@ -0,0 +1,64 @@
+/// Sort direction of `OrderBy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	/// Ascending order
+	Asc,
+	/// Descending order
+	Desc,
+}
+
+
+/// OData `$orderBy` item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBy {
+	/// The field to sort by
+	pub field: String,
+	/// The sort direction
+	pub direction: Direction,
+}
+
+
+impl OrderBy {
+	/// Create the new instance of `OrderBy`
+	pub fn new<T>(field: T, direction: Direction) -> Self where T: Into<String> {
+		OrderBy {
+			field: field.into(),
+			direction: direction,
+		}
+	}
+
+	/// Sort by `field` in ascending order
+	pub fn asc<T>(field: T) -> Self where T: Into<String> {
+		OrderBy::new(field, Direction::Asc)
+	}
+
+	/// Sort by `field` in descending order
+	pub fn desc<T>(field: T) -> Self where T: Into<String> {
+		OrderBy::new(field, Direction::Desc)
+	}
+}
+
+
+impl Into<String> for OrderBy {
+	fn into(self) -> String {
+		match self.direction {
+			Direction::Asc => format!("{} asc", self.field),
+			Direction::Desc => format!("{} desc", self.field),
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::{OrderBy, Direction};
+
+	#[test]
+	fn order_by_into_string() {
+		let asc: String = OrderBy::asc("Name").into();
+		assert_eq!(asc, "Name asc");
+
+		let desc: String = OrderBy::new("CreationDate", Direction::Desc).into();
+		assert_eq!(desc, "CreationDate desc");
+	}
+}
@@ -1,3 +1,8 @@
+use serde_json::Value;
+use url::Url;
+use super::OrderBy;
+
+
 /// OData query parameters
 ///
 /// ## Example
@@ -24,6 +29,9 @@ pub struct Parameters {
 	order_by: Option<Vec<String>>,
 	top: Option<u32>,
 	skip: Option<u32>,
+	skip_token: Option<String>,
+	count: Option<bool>,
+	include_deleted: Option<bool>,
 }
 
 impl Parameters {
@@ -37,6 +45,9 @@ impl Parameters {
 			order_by: None,
 			top: None,
 			skip: None,
+			skip_token: None,
+			count: None,
+			include_deleted: None,
 		}
 	}
 
@@ -145,6 +156,15 @@ impl Parameters {
 		self
 	}
 
+	/// Set `$orderBy` option with the typed items.
+	pub fn order_by_typed(mut self, opt: Vec<OrderBy>) -> Self {
+		let items = opt.into_iter()
+			.map(|v| v.into())
+			.collect();
+		self.order_by = Some(items);
+		self
+	}
+
 	/// Add `$orderBy` option
 	pub fn order_by_add<V>(mut self, opt: V) -> Self where V: Into<String> {
 		match self.order_by {
@@ -172,6 +192,96 @@ impl Parameters {
 		self.skip = Some(opt.into());
 		self
 	}
+
+	/// Set `$skiptoken` option which is the cursor of the next page returned by the server
+	/// in `odata.nextLink`.
+	pub fn skip_token(mut self, token: String) -> Self {
+		self.skip_token = Some(token);
+		self
+	}
+
+	/// Set `$inlinecount` option. If `include` is `true` the response includes the total
+	/// amount of entities in `odata.count` regardless of `$top` and `$skip` options.
+	pub fn count(mut self, include: bool) -> Self {
+		self.count = Some(include);
+		self
+	}
+
+	/// Set `includeDeleted` option. If `include` is `true` items in the Recycle Bin
+	/// are returned along with others.
+	pub fn include_deleted(mut self, include: bool) -> Self {
+		self.include_deleted = Some(include);
+		self
+	}
+
+	/// Combine the parameters with `other` ones. Custom parameters and `$select`, `$expand`,
+	/// `$filter` and `$orderBy` options of `other` are appended to those of `self`, so filters
+	/// of both are applied. `$top`, `$skip`, `$skiptoken`, `$inlinecount` and `includeDeleted`
+	/// options of `other` take precedence over those of `self` if set.
+	pub fn merge(mut self, other: Parameters) -> Self {
+		self.custom = concat(self.custom, other.custom);
+		self.select = concat(self.select, other.select);
+		self.expand = concat(self.expand, other.expand);
+		self.filter = concat(self.filter, other.filter);
+		self.order_by = concat(self.order_by, other.order_by);
+
+		if other.top.is_some() {
+			self.top = other.top;
+		}
+
+		if other.skip.is_some() {
+			self.skip = other.skip;
+		}
+
+		if other.skip_token.is_some() {
+			self.skip_token = other.skip_token;
+		}
+
+		if other.count.is_some() {
+			self.count = other.count;
+		}
+
+		if other.include_deleted.is_some() {
+			self.include_deleted = other.include_deleted;
+		}
+
+		self
+	}
+
+	/// Extract the `$skiptoken` of the next page from `odata.nextLink` of the API response.
+	/// Returns `None` if the response is the last page.
+	pub fn next_skip_token(response: &Value) -> Option<String> {
+		let link = match response.find("odata.nextLink").and_then(|v| v.as_str()) {
+			Some(link) => link,
+			None => return None
+		};
+
+		// The link can be relative to the endpoint
+		let url = match Url::parse(link) {
+			Ok(url) => url,
+			Err(_) => match Url::parse("http://localhost/").and_then(|base| base.join(link)) {
+				Ok(url) => url,
+				Err(_) => return None
+			}
+		};
+
+		url.query_pairs()
+			.find(|&(ref name, _)| name == "$skiptoken")
+			.map(|(_, token)| token.into_owned())
+	}
+}
+
+
+// Append items of `other` list to `list`
+fn concat<T>(list: Option<Vec<T>>, other: Option<Vec<T>>) -> Option<Vec<T>> {
+	match (list, other) {
+		(Some(mut list), Some(other)) => {
+			list.extend(other);
+			Some(list)
+		},
+		(list, None) => list,
+		(None, other) => other,
+	}
 }
 
 
@@ -210,6 +320,18 @@ impl Into<String> for Parameters {
 			options.append_pair("$skip", &num.to_string());
 		};
 
+		if let Some(token) = self.skip_token {
+			options.append_pair("$skiptoken", &token);
+		};
+
+		if self.count == Some(true) {
+			options.append_pair("$inlinecount", "allpages");
+		};
+
+		if self.include_deleted == Some(true) {
+			options.append_pair("includeDeleted", "true");
+		};
+
 		// Convert OData options into URL query
 		options.finish()
 	}
@@ -226,6 +348,7 @@ impl ToString for Parameters {
 #[cfg(test)]
 mod tests {
 	use super::Parameters;
+	use serde_json::Value;
 
 	fn encode_pairs(v: Vec<(&str, &str)>) -> String {
 		use url::form_urlencoded;
@@ -320,6 +443,17 @@ mod tests {
 		assert_eq!(opts, encode_pairs(vec![("$orderBy", "Date asc,Time desc,Id")]));
 	}
 
+	#[test]
+	fn query_options_order_by_typed() {
+		use super::super::{OrderBy, Direction};
+
+		let opts: String = Parameters::new()
+			.order_by_typed(vec![OrderBy::asc("Date"), OrderBy::new("Time", Direction::Desc)])
+			.order_by_add("Id")
+			.into();
+		assert_eq!(opts, encode_pairs(vec![("$orderBy", "Date asc,Time desc,Id")]));
+	}
+
 	#[test]
 	fn query_options_top() {
 		let opts: String = Parameters::new()
@@ -336,6 +470,115 @@ mod tests {
 		assert_eq!(opts, encode_pairs(vec![("$skip", "9")]));
 	}
 
+	#[test]
+	fn query_options_skip_token() {
+		let opts: String = Parameters::new()
+			.top(100u32)
+			.skip_token("fi-100".to_string())
+			.into();
+		assert_eq!(opts, encode_pairs(vec![("$top", "100"), ("$skiptoken", "fi-100")]));
+	}
+
+	#[test]
+	fn query_options_count() {
+		let opts: String = Parameters::new()
+			.top(10u32)
+			.count(true)
+			.into();
+		assert_eq!(opts, encode_pairs(vec![("$top", "10"), ("$inlinecount", "allpages")]));
+
+		let opts: String = Parameters::new()
+			.count(false)
+			.into();
+		assert_eq!(opts, "".to_string());
+	}
+
+	#[test]
+	fn query_options_include_deleted() {
+		let opts: String = Parameters::new()
+			.top(10u32)
+			.include_deleted(true)
+			.into();
+		assert_eq!(opts, encode_pairs(vec![("$top", "10"), ("includeDeleted", "true")]));
+
+		let opts: String = Parameters::new()
+			.include_deleted(false)
+			.into();
+		assert_eq!(opts, "".to_string());
+	}
+
+	#[test]
+	fn merge_concatenates() {
+		let opts: String = Parameters::new()
+			.custom(vec![("a", "1")])
+			.select(vec!["Id", "Name"])
+			.expand(vec!["Children"])
+			.filter(vec!["A eq B"])
+			.merge(Parameters::new()
+				.custom(vec![("b", "2")])
+				.select(vec!["Size"])
+				.filter(vec!["C eq D"])
+				.order_by(vec!["Name asc"]))
+			.into();
+
+		assert_eq!(opts, encode_pairs(vec![
+				("a", "1"),
+				("b", "2"),
+				("$select", "Id,Name,Size"),
+				("$expand", "Children"),
+				("$filter", "A eq B and C eq D"),
+				("$orderBy", "Name asc")
+			]));
+	}
+
+	#[test]
+	fn merge_overrides() {
+		let defaults = Parameters::new()
+			.top(100u32)
+			.skip(20u32)
+			.include_deleted(true);
+
+		let opts: String = defaults.clone()
+			.merge(Parameters::new()
+				.top(10u32)
+				.count(true))
+			.into();
+		assert_eq!(opts, encode_pairs(vec![("$top", "10"), ("$skip", "20"), ("$inlinecount", "allpages"), ("includeDeleted", "true")]));
+
+		let opts: String = defaults
+			.merge(Parameters::new())
+			.into();
+		assert_eq!(opts, encode_pairs(vec![("$top", "100"), ("$skip", "20"), ("includeDeleted", "true")]));
+	}
+
+	#[test]
+	fn merge_turns_flags_off() {
+		let defaults = Parameters::new()
+			.count(true)
+			.include_deleted(true);
+
+		let opts: String = defaults
+			.merge(Parameters::new()
+				.count(false)
+				.include_deleted(false))
+			.into();
+		assert_eq!(opts, "");
+	}
+
+	#[test]
+	fn next_skip_token() {
+		use serde_json;
+
+		let response: Value = serde_json::from_str("{\"odata.count\":500,\"odata.nextLink\":\"https://tenant.sf-api.com/sf/v3/Items(fo123)/Children?$top=100&$skiptoken=fi-100\",\"value\":[]}").unwrap();
+		assert_eq!(Parameters::next_skip_token(&response), Some("fi-100".to_string()));
+
+		let response: Value = serde_json::from_str("{\"odata.nextLink\":\"Items(fo123)/Children?%24skiptoken=fi%2B200\"}").unwrap();
+		assert_eq!(Parameters::next_skip_token(&response), Some("fi+200".to_string()));
+
+		let response: Value = serde_json::from_str("{\"odata.count\":5,\"value\":[]}").unwrap();
+		assert_eq!(Parameters::next_skip_token(&response), None);
+	}
+
 	#[test]
 	fn query_options_all() {
 		let opts: String = Parameters::new()